@@ -0,0 +1,154 @@
+## App
+
+nav-timeline = My timeline
+nav-language = Language
+
+nav-settings = Settings
+nav-back = ← Back to joys
+
+## Joy form
+
+joy-form-start-title = Ready to reflect?
+joy-form-start-subtitle = Turn today's frustrations into tomorrow's connections
+joy-form-start = Start
+joy-form-frustration-prompt = Write about something that upset or annoyed you today
+joy-form-frustration-placeholder = What frustrated you?
+joy-form-context-prompt = What was the context? What were you doing?
+joy-form-context-placeholder = Describe the situation...
+joy-form-joy-prompt = What joy do you find in that context or activity?
+joy-form-joy-placeholder = Think about the positive side...
+joy-form-next = Next
+joy-form-submit = Submit
+# Follows the live character count, e.g. "42 / 100 characters".
+joy-form-character-limit = / { $count } { $count ->
+    [one] character
+   *[other] characters
+}
+
+## Joy card
+
+joy-card-edited = edited
+joy-card-edit = Edit
+joy-card-save = Save
+joy-card-cancel = Cancel
+joy-card-delete = Delete
+joy-card-hide = Hide
+joy-card-hide-title = Hide this joy from my feed
+joy-card-hide-author = Hide author
+joy-card-hide-author-title = Hide everything by this author
+joy-card-report = Report
+joy-card-report-title = Report this joy as abusive
+joy-card-report-confirm = Report this joy as abusive?
+reaction-smile = This made me smile
+reaction-relate = I can relate
+reaction-hug = Sending a hug
+
+## Location picker
+
+place-near = near { $place }
+location-current = Showing joys around { $place }
+location-unknown = We don't know where you are yet
+location-hint = Can't share your location? Pick the nearest town or city instead.
+location-search-placeholder = Search for a place...
+location-no-results = No places match "{ $query }"
+
+## Timeline
+
+timeline-title = Where I wrote my joys
+timeline-consent = Keep a history of my locations
+timeline-hint-enabled = Your recent locations are shown below. Turning this off deletes them.
+timeline-hint-disabled = Only the places you wrote your joys are shown.
+timeline-empty = Nothing here yet.
+timeline-no-location = No location
+
+## Relative dates
+
+date-just-now = Just now
+date-seconds-ago = { $count } { $count ->
+    [one] second
+   *[other] seconds
+} ago
+date-minutes-ago = { $count } { $count ->
+    [one] minute
+   *[other] minutes
+} ago
+date-yesterday = { $time } Yesterday
+weekday-mon = Mon
+weekday-tue = Tue
+weekday-wed = Wed
+weekday-thu = Thu
+weekday-fri = Fri
+weekday-sat = Sat
+weekday-sun = Sun
+month-jan = Jan
+month-feb = Feb
+month-mar = Mar
+month-apr = Apr
+month-may = May
+month-jun = Jun
+month-jul = Jul
+month-aug = Aug
+month-sep = Sep
+month-oct = Oct
+month-nov = Nov
+month-dec = Dec
+
+## Distances
+# $distance is already formatted for the locale; $count selects the plural.
+
+distance-right-here = Right here
+distance-metres = { $distance }m
+distance-kilometres = { $distance } km
+distance-thousand-kilometres = { $distance }k km
+distance-yards = { $distance } yd
+distance-one-mile = 1 mile
+distance-miles = { $distance } { $count ->
+    [one] mile
+   *[other] miles
+}
+distance-thousand-miles = { $distance }k miles
+
+## Settings
+
+settings-title = Settings
+settings-saved = Your settings have been saved.
+settings-automatic = Automatic
+settings-language = Language
+settings-units = Distance units
+units-metric = Kilometres
+units-imperial = Miles
+settings-radius = Show joys within
+settings-radius-option = { $count } km
+settings-ranking = Order my feed by
+ranking-nearest = Nearest first
+ranking-newest = Newest first
+ranking-popular = Most reactions first
+settings-share-location = Share my location
+settings-share-location-hint = When this is off we stop tracking your location and new joys are posted without one. You can still pick a place by hand.
+settings-save = Save
+
+settings-tokens-title = API tokens
+settings-tokens-hint = Personal access tokens let other apps use the API at /api/v1 as you. Send one as "Authorization: Bearer <token>".
+settings-token-created = Copy your new token now. You won't be able to see it again.
+settings-token-created-on = created { $date }
+settings-token-expires = expires { $date }
+settings-token-expired = expired { $date }
+settings-token-last-used = last used { $date }
+settings-token-revoke = Revoke
+settings-token-name = Token name
+settings-token-lifetime = Expires after
+settings-token-days = { $count ->
+    [one] { $count } day
+   *[other] { $count } days
+}
+settings-token-never = Never
+settings-token-create = Create token
+settings-data-title = Your data
+settings-data-hint = Download everything we keep about you: your joys with their frustration and context, your locations and your settings.
+settings-data-json = Download as JSON
+settings-data-zip = Download as ZIP
+settings-erase-hint = Erasing your account deletes your joys, locations, reactions, settings and API tokens for good, and signs you out. This can't be undone.
+settings-erase-confirm = I understand that my data will be deleted permanently
+settings-erase = Erase my account
+scope-read = Read joys
+scope-write = Post joys and update my location
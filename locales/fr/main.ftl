@@ -0,0 +1,153 @@
+## App
+
+nav-timeline = Ma chronologie
+nav-language = Langue
+
+nav-settings = Paramètres
+nav-back = ← Retour aux joies
+
+## Joy form
+
+joy-form-start-title = Prêt à réfléchir ?
+joy-form-start-subtitle = Transformez les frustrations d’aujourd’hui en liens pour demain
+joy-form-start = Commencer
+joy-form-frustration-prompt = Écrivez quelque chose qui vous a contrarié ou agacé aujourd’hui
+joy-form-frustration-placeholder = Qu’est-ce qui vous a frustré ?
+joy-form-context-prompt = Quel était le contexte ? Que faisiez-vous ?
+joy-form-context-placeholder = Décrivez la situation...
+joy-form-joy-prompt = Quelle joie trouvez-vous dans ce contexte ou cette activité ?
+joy-form-joy-placeholder = Pensez au côté positif...
+joy-form-next = Suivant
+joy-form-submit = Envoyer
+joy-form-character-limit = / { $count } { $count ->
+    [one] caractère
+   *[other] caractères
+}
+
+## Joy card
+
+joy-card-edited = modifié
+joy-card-edit = Modifier
+joy-card-save = Enregistrer
+joy-card-cancel = Annuler
+joy-card-delete = Supprimer
+joy-card-hide = Masquer
+joy-card-hide-title = Masquer cette joie de mon fil
+joy-card-hide-author = Masquer l’auteur
+joy-card-hide-author-title = Masquer tout ce que publie cette personne
+joy-card-report = Signaler
+joy-card-report-title = Signaler cette joie comme abusive
+joy-card-report-confirm = Signaler cette joie comme abusive ?
+reaction-smile = M’a fait sourire
+reaction-relate = Je me reconnais
+reaction-hug = Je vous envoie un câlin
+
+## Location picker
+
+place-near = près de { $place }
+location-current = Joies autour de { $place }
+location-unknown = Nous ne savons pas encore où vous êtes
+location-hint = Vous ne pouvez pas partager votre position ? Choisissez la ville la plus proche.
+location-search-placeholder = Rechercher un lieu...
+location-no-results = Aucun lieu ne correspond à « { $query } »
+
+## Timeline
+
+timeline-title = Où j’ai écrit mes joies
+timeline-consent = Conserver un historique de mes positions
+timeline-hint-enabled = Vos positions récentes sont affichées ci-dessous. Désactiver cette option les supprime.
+timeline-hint-disabled = Seuls les lieux où vous avez écrit vos joies sont affichés.
+timeline-empty = Rien pour l’instant.
+timeline-no-location = Pas de position
+
+## Relative dates
+# Worded like Intl.RelativeTimeFormat("fr"), which takes over on the client.
+
+date-just-now = maintenant
+date-seconds-ago = il y a { $count } { $count ->
+    [one] seconde
+   *[other] secondes
+}
+date-minutes-ago = il y a { $count } { $count ->
+    [one] minute
+   *[other] minutes
+}
+date-yesterday = hier { $time }
+weekday-mon = lun.
+weekday-tue = mar.
+weekday-wed = mer.
+weekday-thu = jeu.
+weekday-fri = ven.
+weekday-sat = sam.
+weekday-sun = dim.
+month-jan = janv.
+month-feb = févr.
+month-mar = mars
+month-apr = avr.
+month-may = mai
+month-jun = juin
+month-jul = juil.
+month-aug = août
+month-sep = sept.
+month-oct = oct.
+month-nov = nov.
+month-dec = déc.
+
+## Distances
+
+distance-right-here = Ici même
+distance-metres = { $distance } m
+distance-kilometres = { $distance } km
+distance-thousand-kilometres = { $distance } k km
+distance-yards = { $distance } yards
+distance-one-mile = 1 mille
+distance-miles = { $distance } { $count ->
+    [one] mille
+   *[other] milles
+}
+distance-thousand-miles = { $distance } k milles
+
+## Settings
+
+settings-title = Paramètres
+settings-saved = Vos paramètres ont été enregistrés.
+settings-automatic = Automatique
+settings-language = Langue
+settings-units = Unités de distance
+units-metric = Kilomètres
+units-imperial = Milles
+settings-radius = Afficher les joies dans un rayon de
+settings-radius-option = { $count } km
+settings-ranking = Trier mon fil par
+ranking-nearest = Les plus proches
+ranking-newest = Les plus récentes
+ranking-popular = Les plus appréciées
+settings-share-location = Partager ma position
+settings-share-location-hint = Si cette option est désactivée, nous ne suivons plus votre position et les nouvelles joies sont publiées sans position. Vous pouvez toujours choisir un lieu à la main.
+settings-save = Enregistrer
+
+settings-tokens-title = Jetons d'API
+settings-tokens-hint = Les jetons d'accès personnels permettent à d'autres applications d'utiliser l'API /api/v1 en votre nom. Envoyez-en un avec « Authorization: Bearer <jeton> ».
+settings-token-created = Copiez votre nouveau jeton maintenant. Vous ne pourrez plus le voir ensuite.
+settings-token-created-on = créé le { $date }
+settings-token-expires = expire le { $date }
+settings-token-expired = expiré le { $date }
+settings-token-last-used = utilisé le { $date }
+settings-token-revoke = Révoquer
+settings-token-name = Nom du jeton
+settings-token-lifetime = Expire après
+settings-token-days = { $count ->
+    [one] { $count } jour
+   *[other] { $count } jours
+}
+settings-token-never = Jamais
+settings-token-create = Créer le jeton
+settings-data-title = Vos données
+settings-data-hint = Téléchargez tout ce que nous conservons à votre sujet : vos joies avec leur frustration et leur contexte, vos positions et vos réglages.
+settings-data-json = Télécharger en JSON
+settings-data-zip = Télécharger en ZIP
+settings-erase-hint = Effacer votre compte supprime définitivement vos joies, positions, réactions, réglages et jetons d'API, et vous déconnecte. Cette action est irréversible.
+settings-erase-confirm = Je comprends que mes données seront supprimées définitivement
+settings-erase = Effacer mon compte
+scope-read = Lire les joies
+scope-write = Publier des joies et mettre à jour ma position
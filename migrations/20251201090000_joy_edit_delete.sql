@@ -0,0 +1,4 @@
+ALTER TABLE joys ADD COLUMN IF NOT EXISTS updated TIMESTAMPTZ;
+ALTER TABLE joys ADD COLUMN IF NOT EXISTS deleted TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS joys_live_idx ON joys (created) WHERE deleted IS NULL;
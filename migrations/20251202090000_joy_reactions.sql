@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS joy_reactions (
+    joy_id UUID NOT NULL REFERENCES joys(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('smile', 'relate', 'hug')),
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (joy_id, user_id)
+);
@@ -0,0 +1,5 @@
+ALTER TABLE joys ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'approved'
+    CHECK (status IN ('approved', 'held', 'rejected'));
+ALTER TABLE joys ADD COLUMN IF NOT EXISTS moderation_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS joys_held_idx ON joys (created) WHERE status = 'held';
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS reports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    joy_id UUID NOT NULL REFERENCES joys(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (joy_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS user_hides (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joy_id UUID REFERENCES joys(id) ON DELETE CASCADE,
+    author_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((joy_id IS NULL) <> (author_id IS NULL)),
+    UNIQUE (user_id, joy_id),
+    UNIQUE (user_id, author_id)
+);
+
+CREATE INDEX IF NOT EXISTS user_hides_user_idx ON user_hides (user_id);
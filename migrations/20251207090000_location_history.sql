@@ -0,0 +1,14 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS accuracy DOUBLE PRECISION;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS location_history BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE joys ADD COLUMN IF NOT EXISTS accuracy DOUBLE PRECISION;
+
+CREATE TABLE IF NOT EXISTS user_locations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    point GEOGRAPHY(Point, 4326) NOT NULL,
+    accuracy DOUBLE PRECISION
+);
+
+CREATE INDEX IF NOT EXISTS user_locations_user_created_idx ON user_locations (user_id, created DESC);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    -- NULL units / locale follow the browser's language.
+    units TEXT CHECK (units IN ('metric', 'imperial')),
+    locale TEXT,
+    radius DOUBLE PRECISION NOT NULL DEFAULT 1000000 CHECK (radius > 0),
+    ranking TEXT NOT NULL DEFAULT 'nearest' CHECK (ranking IN ('nearest', 'newest', 'popular')),
+    share_location BOOLEAN NOT NULL DEFAULT TRUE,
+    updated TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- SHA-256 of the secret; the secret itself is only shown once.
+    token_hash TEXT NOT NULL UNIQUE,
+    -- The first characters of the secret, so users can tell tokens apart.
+    prefix TEXT NOT NULL,
+    scopes TEXT[] NOT NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires TIMESTAMPTZ,
+    last_used TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS api_tokens_user_idx ON api_tokens (user_id);
@@ -0,0 +1,10 @@
+-- The joy form's progress, one draft per user so a reload picks up where
+-- the form left off. Submitting the form consumes the row.
+CREATE TABLE IF NOT EXISTS joy_drafts (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    step SMALLINT NOT NULL DEFAULT 0 CHECK (step BETWEEN 0 AND 3),
+    frustration TEXT NOT NULL DEFAULT '',
+    context TEXT NOT NULL DEFAULT '',
+    joy TEXT NOT NULL DEFAULT '',
+    updated TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- When the user last made a request with their session, so anonymous users
+-- whose sessions have expired can be garbage-collected.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS users_last_seen_idx ON users (last_seen) WHERE role = 'user';
@@ -0,0 +1,4 @@
+-- Joys anonymised by the retention job keep their text but lose their author.
+ALTER TABLE joys ALTER COLUMN user_id DROP NOT NULL;
+
+CREATE INDEX IF NOT EXISTS joys_created_idx ON joys (created);
@@ -0,0 +1,7 @@
+-- What is left of an erased user: no personal data, just proof the erasure
+-- happened. Deliberately not a foreign key, since the user row is gone.
+CREATE TABLE IF NOT EXISTS user_tombstones (
+    user_id UUID PRIMARY KEY,
+    erased TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    joys INTEGER NOT NULL
+);
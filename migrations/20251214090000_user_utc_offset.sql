@@ -0,0 +1,4 @@
+-- The browser's offset from UTC in minutes, so times rendered on the server
+-- match the user's clock. NULL renders in UTC.
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS utc_offset SMALLINT
+    CHECK (utc_offset BETWEEN -1080 AND 1080);
@@ -0,0 +1,67 @@
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::service::state::AppState;
+
+pub mod v1;
+
+/// The body of every API error response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    /// The HTTP status code, repeated for clients that only see the body.
+    #[schema(example = 404)]
+    pub status: u16,
+    #[schema(example = "joy not found")]
+    pub error: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, error: impl Into<String>) -> Self {
+        Self { status: status.as_u16(), error: error.into() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(self)).into_response()
+    }
+}
+
+/// Lets handlers use `?` on the `(StatusCode, String)` errors the services
+/// and HTML handlers already return.
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, error): (StatusCode, String)) -> Self {
+        if status.is_server_error() {
+            tracing::error!(%status, error, "API request failed");
+            return Self::new(status, "internal error");
+        }
+        Self::new(status, error)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().nest("/api/v1", v1::router())
+}
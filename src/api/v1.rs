@@ -0,0 +1,219 @@
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::api::ApiError;
+use crate::service::auth::RequireAccount;
+use crate::service::joy::{self, Joy, NewJoy, Page};
+use crate::service::state::AppState;
+use crate::service::user::record_location;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Joyus API", version = "1", description = "JSON access to the joy feed."),
+    paths(list_joys, create_joy, get_joy, update_location),
+    components(schemas(ApiError)),
+    modifiers(&BearerToken),
+    security((), ("token" = [])),
+    tags(
+        (name = "joys", description = "Reading and posting joys"),
+        (name = "location", description = "The user's current location"),
+    ),
+)]
+pub struct ApiDoc;
+
+/// Documents personal access tokens. Requests without one fall back to an
+/// existing session cookie, as in the browser; with neither they get a 401
+/// rather than a freshly minted user.
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "token",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// At most this many joys, up to 100. Defaults to 20.
+    limit: Option<i64>,
+    /// Joys to skip, from a previous page's `next_offset`.
+    offset: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct JoyPage {
+    /// Other users' joys carry rounded coordinates and no accuracy.
+    items: Vec<Joy>,
+    limit: i64,
+    offset: i64,
+    /// The offset of the next page, if there is one.
+    next_offset: Option<i64>,
+}
+
+/// The user's feed: joys within their radius, in their preferred order.
+#[utoipa::path(
+    get,
+    path = "/api/v1/joys",
+    tag = "joys",
+    params(PageQuery),
+    responses(
+        (status = 200, description = "A page of the feed", body = JoyPage),
+        (status = 400, description = "Invalid paging", body = ApiError),
+        (status = 401, description = "No token and no session", body = ApiError),
+    ),
+)]
+pub async fn list_joys(
+    State(state): State<AppState>,
+    current: Result<RequireAccount, (StatusCode, String)>,
+    query: Result<Query<PageQuery>, QueryRejection>,
+) -> Result<Json<JoyPage>, ApiError> {
+    let Query(query) = query?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, format!("limit must be between 1 and {}", MAX_PAGE_SIZE)));
+    }
+    if offset < 0 {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "offset cannot be negative"));
+    }
+
+    let RequireAccount(user) = current?;
+    let preferences = state.preferences.get(&user.id).await
+        .map_err(crate::service::internal_error)?;
+
+    // One extra row tells us whether there is another page.
+    let mut items = state.joys
+        .list_for_user(&user.id, &preferences, Page { limit: Some(limit + 1), offset })
+        .await
+        .map_err(crate::service::internal_error)?;
+    let next_offset = (items.len() as i64 > limit).then_some(offset + limit);
+    items.truncate(limit as usize);
+
+    Ok(Json(JoyPage { items, limit, offset, next_offset }))
+}
+
+/// Posts a joy. It may be held for review, in which case only its author
+/// sees it until a moderator approves it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/joys",
+    tag = "joys",
+    request_body = NewJoy,
+    responses(
+        (status = 201, description = "The stored joy as its author sees it, with its exact location and private fields", body = Joy),
+        (status = 400, description = "Invalid or rejected joy", body = ApiError),
+        (status = 403, description = "The user is banned", body = ApiError),
+        (status = 401, description = "No token and no session", body = ApiError),
+    ),
+)]
+pub async fn create_joy(
+    State(state): State<AppState>,
+    current: Result<RequireAccount, (StatusCode, String)>,
+    body: Result<Json<NewJoy>, JsonRejection>,
+) -> Result<(StatusCode, Json<Joy>), ApiError> {
+    let Json(form) = body?;
+    let RequireAccount(user) = current?;
+    let joy = joy::submit(&state, &user, form).await?;
+    Ok((StatusCode::CREATED, Json(joy)))
+}
+
+/// A single joy, if the user can see it.
+#[utoipa::path(
+    get,
+    path = "/api/v1/joys/{id}",
+    tag = "joys",
+    params(("id" = Uuid, Path, description = "The joy's id")),
+    responses(
+        (status = 200, description = "The joy; exact location and private fields only if the user wrote it", body = Joy),
+        (status = 404, description = "No such joy, or it is hidden from the user", body = ApiError),
+        (status = 401, description = "No token and no session", body = ApiError),
+    ),
+)]
+pub async fn get_joy(
+    State(state): State<AppState>,
+    current: Result<RequireAccount, (StatusCode, String)>,
+    id: Result<Path<Uuid>, PathRejection>,
+) -> Result<Json<Joy>, ApiError> {
+    let Path(id) = id?;
+    let RequireAccount(user) = current?;
+    let joy = state.joys.get_for_user(id, user.id).await
+        .map_err(crate::service::internal_error)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "joy not found"))?;
+    Ok(Json(joy))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LocationUpdate {
+    longitude: f64,
+    latitude: f64,
+    /// Radius of uncertainty in metres.
+    accuracy: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LocationRecorded {
+    /// False if the user has turned location sharing off, or has not moved
+    /// far enough since their last fix for it to matter.
+    recorded: bool,
+}
+
+/// Reports where the user is now, which the feed is centred on.
+#[utoipa::path(
+    put,
+    path = "/api/v1/location",
+    tag = "location",
+    request_body = LocationUpdate,
+    responses(
+        (status = 200, description = "Whether the fix was stored", body = LocationRecorded),
+        (status = 400, description = "Missing or out of range coordinates", body = ApiError),
+        (status = 401, description = "No token and no session", body = ApiError),
+    ),
+)]
+pub async fn update_location(
+    State(state): State<AppState>,
+    current: Result<RequireAccount, (StatusCode, String)>,
+    body: Result<Json<LocationUpdate>, JsonRejection>,
+) -> Result<Json<LocationRecorded>, ApiError> {
+    let Json(form) = body?;
+    if !(-180.0..=180.0).contains(&form.longitude) || !(-90.0..=90.0).contains(&form.latitude) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "coordinates out of range"));
+    }
+
+    let RequireAccount(user) = current?;
+    let recorded = record_location(&state, user.id, form.longitude, form.latitude, form.accuracy).await
+        .map_err(crate::service::internal_error)?;
+    Ok(Json(LocationRecorded { recorded }))
+}
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+async fn not_found() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, "no such endpoint")
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi))
+        .route("/joys", get(list_joys).post(create_joy))
+        .route("/joys/:id", get(get_joy))
+        .route("/location", put(update_location))
+        .fallback(not_found)
+}
@@ -0,0 +1,8 @@
+import {Component} from "../component";
+
+export class Admin extends Component {
+    protected signals = {
+        banReason: '',
+    };
+}
+window.customElements.define('app-admin', Admin);
@@ -0,0 +1,185 @@
+use askama::Template;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use axum::response::Html;
+use axum::routing::post;
+use axum::Router;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::component::{Component, Shell};
+use crate::service::admin::{AdminUser, AuditEntry, QueuedJoy, UserDetail};
+use crate::service::locale::Locale;
+use crate::service::moderation::Status;
+use crate::service::retention::{RetentionPolicy, RetentionReport};
+use crate::service::sse::Patch;
+use crate::service::state::AppState;
+use crate::service::user::User;
+
+const AUDIT_LOG_LIMIT: i64 = 50;
+
+#[derive(Template)]
+#[template(path = "component/admin/admin.html")]
+pub struct Admin {
+    queue: Vec<QueuedJoy>,
+    audit: Vec<AuditEntry>,
+    lookup: String,
+    user: Option<UserDetail>,
+    error: Option<String>,
+    retention: RetentionPolicy,
+    /// What the next retention run would do.
+    pending: RetentionReport,
+}
+
+impl Admin {
+    async fn load(state: &AppState, lookup: Option<String>) -> Result<Self, (StatusCode, String)> {
+        let queue = state.admin.queue().await.map_err(crate::service::internal_error)?;
+        let audit = state.admin.audit_log(AUDIT_LOG_LIMIT).await.map_err(crate::service::internal_error)?;
+
+        let lookup = lookup.map(|l| l.trim().to_string()).unwrap_or_default();
+        let (user, error) = if lookup.is_empty() {
+            (None, None)
+        } else {
+            match Uuid::parse_str(&lookup) {
+                Ok(id) => match state.admin.find_user(&id).await.map_err(crate::service::internal_error)? {
+                    Some(user) => (Some(user), None),
+                    None => (None, Some(format!("No user with id {}", id))),
+                },
+                Err(_) => (None, Some(format!("\"{}\" is not a valid user id", lookup))),
+            }
+        };
+
+        let retention = *state.retention.policy();
+        let pending = state.retention.report().await.map_err(crate::service::internal_error)?;
+
+        Ok(Admin { queue, audit, lookup, user, error, retention, pending })
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub struct Lookup {
+    user: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl Component for Admin {
+    const NAME: &'static str = "admin";
+    type Props = Lookup;
+
+    async fn build(state: &AppState, user: &User, _locale: Locale, props: Lookup) -> Result<Self, (StatusCode, String)> {
+        if !user.is_admin() {
+            return Err((StatusCode::FORBIDDEN, "admin role required".to_string()));
+        }
+        Self::load(state, props.user).await
+    }
+
+    /// The admin pages are not translated, so they are always served in the
+    /// default language.
+    fn page(&self, shell: &Shell) -> Result<Html<String>, String> {
+        let Html(app) = self.fragment()?;
+        crate::Index { lang: Locale::default().tag(), csrf: &shell.csrf, nonce: &shell.nonce, app }
+            .render()
+            .map(Html)
+            .map_err(|e| e.to_string())
+    }
+}
+
+async fn review(
+    state: &AppState,
+    admin: AdminUser,
+    id: Uuid,
+    status: Status,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let AdminUser(moderator) = admin;
+    if !state.admin.review(&id, &moderator.id, status).await.map_err(crate::service::internal_error)? {
+        return Err((StatusCode::NOT_FOUND, "joy not found".to_string()));
+    }
+
+    // Approved joys may be new to most feeds; rejected ones only need retracting.
+    let patch = match status {
+        Status::Approved => Patch::JoyCards { user_id: None },
+        _ => Patch::JoyCard(id),
+    };
+    if let Err(e) = state.sse.publish(patch) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Admin::load(state, None).await?
+        .fragment()
+        .map_err(crate::service::internal_error)
+}
+
+pub async fn approve(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    Path(id): Path<Uuid>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    review(&state, admin, id, Status::Approved).await
+}
+
+pub async fn reject(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    Path(id): Path<Uuid>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    review(&state, admin, id, Status::Rejected).await
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BanForm {
+    ban_reason: Option<String>,
+}
+
+async fn set_banned(
+    state: &AppState,
+    admin: AdminUser,
+    user_id: Uuid,
+    banned: bool,
+    reason: Option<String>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let AdminUser(moderator) = admin;
+    if moderator.id == user_id {
+        return Err((StatusCode::BAD_REQUEST, "you cannot ban yourself".to_string()));
+    }
+
+    let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    if !state.admin.set_banned(&user_id, &moderator.id, banned, reason).await.map_err(crate::service::internal_error)? {
+        return Err((StatusCode::NOT_FOUND, "user not found".to_string()));
+    }
+
+    Admin::load(state, Some(user_id.to_string())).await?
+        .fragment()
+        .map_err(crate::service::internal_error)
+}
+
+pub async fn ban(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+    form: Option<Json<BanForm>>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let Json(form) = form.unwrap_or_default();
+    set_banned(&state, admin, user_id, true, form.ban_reason).await
+}
+
+pub async fn unban(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    set_banned(&state, admin, user_id, false, None).await
+}
+
+pub fn router() -> Router<AppState> {
+    Admin::router()
+        .nest(
+            "/admin",
+            Router::new()
+                .route("/joys/:id/approve", post(approve))
+                .route("/joys/:id/reject", post(reject))
+                .route("/users/:id/ban", post(ban))
+                .route("/users/:id/unban", post(unban)),
+        )
+}
@@ -1,43 +1,76 @@
 use askama::Template;
-use axum::extract::State;
-use axum::response::Html;
-use axum::routing::get;
 use axum::http::StatusCode;
-use axum::Router;
-use tower_sessions::Session;
+use axum::response::Html;
 
+use crate::component::flash::Flash;
+use crate::component::joy_cards::JoyCards;
+use crate::component::joy_form::JoyForm;
+use crate::component::location_picker::LocationPicker;
+use crate::component::{Component, NoProps};
 use crate::service::{
+    locale::Locale,
     state::AppState,
+    user::User,
 };
 
 #[derive(Template)]
 #[template(path = "component/app/app.html")]
 pub struct App {
+    locale: Locale,
+    locales: &'static [Locale],
+    share_location: bool,
+    /// The stored offset from UTC in minutes, for the client to correct.
+    utc_offset: Option<i16>,
+    csrf: String,
+    flash: String,
+    location_picker: String,
     joy_form: String,
     joy_cards: String,
 }
 
-pub async fn show(State(state): State<AppState>, session: Session) -> Result<Html<String>, (StatusCode, String)> {
-    let user = state
-        .users
-        .get_or_create_session_user(session.clone())
-        .await
+impl App {
+    /// Shows `flash` on load, for full-page responses to plain form posts.
+    pub fn with_flash(self, flash: Flash) -> Result<Self, String> {
+        let Html(flash) = flash.render_html()?;
+        Ok(Self { flash, ..self })
+    }
+}
+
+/// Renders a child component into the markup its parent embeds.
+async fn child<C: Component>(state: &AppState, user: &User, locale: Locale, props: C::Props) -> Result<String, (StatusCode, String)> {
+    let Html(html) = C::build(state, user, locale, props)
+        .await?
+        .fragment()
         .map_err(crate::service::internal_error)?;
+    Ok(html)
+}
 
-    let Html(joy_form) = crate::component::joy_form::render_for_session(&state, session)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+#[async_trait::async_trait]
+impl Component for App {
+    const NAME: &'static str = "app";
+    type Props = NoProps;
 
-    let Html(joy_cards) = crate::component::joy_cards::render_for_user(&state, user.id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    async fn build(state: &AppState, user: &User, locale: Locale, _props: NoProps) -> Result<Self, (StatusCode, String)> {
+        let preferences = state
+            .preferences
+            .get(&user.id)
+            .await
+            .map_err(crate::service::internal_error)?;
 
-    let app = App { joy_form, joy_cards };
-    let html = app.render().map_err(crate::service::internal_error)?;
-    Ok(Html(html))
-}
+        let Html(flash) = Flash::empty()
+            .render_html()
+            .map_err(crate::service::internal_error)?;
 
-pub fn router() -> Router<AppState> {
-    Router::new()
-        .nest("/app", Router::new().route("/", get(show)))
+        Ok(App {
+            locale,
+            locales: &Locale::ALL,
+            share_location: preferences.share_location,
+            utc_offset: preferences.utc_offset_minutes(),
+            csrf: user.csrf.clone(),
+            flash,
+            location_picker: child::<LocationPicker>(state, user, locale, NoProps::default()).await?,
+            joy_form: child::<JoyForm>(state, user, locale, NoProps::default()).await?,
+            joy_cards: child::<JoyCards>(state, user, locale, NoProps::default()).await?,
+        })
+    }
 }
@@ -1,29 +1,7 @@
 import {apply} from '@engine';
-import {DATASTAR_ELEMENT_PATCH_EVENT} from "@engine/consts";
-import type {DatastarElementPatchEvent} from "@engine/types";
-
-const scripts = new WeakSet<HTMLScriptElement>()
-for (const script of document.querySelectorAll('script')) {
-    scripts.add(script)
-}
-
-const execute = (target: Element): void => {
-    const elScripts =
-        target instanceof HTMLScriptElement
-            ? [target]
-            : target.querySelectorAll('script')
-    for (const old of elScripts) {
-        if (!scripts.has(old)) {
-            const script = document.createElement('script')
-            for (const { name, value } of old.attributes) {
-                script.setAttribute(name, value)
-            }
-            script.text = old.text
-            old.replaceWith(script)
-            scripts.add(script)
-        }
-    }
-}
+import {getStoreFor, mergePatch} from "@engine/signals";
+import {DATASTAR_ELEMENT_PATCH_EVENT, DATASTAR_ELEMENT_SIGNALS_EVENT} from "@engine/consts";
+import type {DatastarElementPatchEvent, DatastarElementSignalsEvent} from "@engine/types";
 
 export class Component extends HTMLElement {
     protected signals = {};
@@ -38,21 +16,34 @@ export class Component extends HTMLElement {
         }
         apply(this);
         document.addEventListener(DATASTAR_ELEMENT_PATCH_EVENT, this.handlePatchEvent.bind(this));
+        document.addEventListener(DATASTAR_ELEMENT_SIGNALS_EVENT, this.handleSignalsEvent.bind(this));
     }
 
     disconnectedCallback() {
         document.removeEventListener(DATASTAR_ELEMENT_PATCH_EVENT, this.handlePatchEvent.bind(this));
+        document.removeEventListener(DATASTAR_ELEMENT_SIGNALS_EVENT, this.handleSignalsEvent.bind(this));
     }
 
-    handlePatchEvent(event: CustomEvent<DatastarElementPatchEvent>) {
+    handleSignalsEvent(event: CustomEvent<DatastarElementSignalsEvent>) {
         if (event.detail.id === this.id) {
+            mergePatch(event.detail.signals, getStoreFor(this), {ifMissing: event.detail.onlyIfMissing});
+        }
+    }
+
+    handlePatchEvent(event: CustomEvent<DatastarElementPatchEvent>) {
+        if (event.detail.id !== this.id) {
+            return;
+        }
+        if (event.detail.mode === 'remove') {
+            this.remove();
+        } else if (event.detail.element) {
             this.applyPatch(event.detail.element);
         }
     }
 
+    // Scripts in patched markup are deliberately left inert: components
+    // render none, and running them would trust whatever reached the patch.
     applyPatch(element: Element) {
-        const cloned = element.cloneNode(true) as Element
-        execute(cloned)
-        this.replaceWith(cloned);
+        this.replaceWith(element.cloneNode(true));
     }
 }
@@ -0,0 +1,5 @@
+import {Component} from "../component";
+
+export class Flash extends Component {
+}
+window.customElements.define('app-flash', Flash);
@@ -0,0 +1,40 @@
+use askama::Template;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+
+/// A single dismissable message slot. Error responses render it so that
+/// Datastar patches the message into the page in place of a silent failure.
+#[derive(Template)]
+#[template(path = "component/flash/flash.html")]
+pub struct Flash {
+    pub level: &'static str,
+    pub message: Option<String>,
+}
+
+impl Flash {
+    pub fn empty() -> Self {
+        Self { level: "info", message: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { level: "error", message: Some(message.into()) }
+    }
+
+    pub fn render_html(&self) -> Result<Html<String>, String> {
+        self.render().map(Html).map_err(|e| e.to_string())
+    }
+
+    /// An error response whose body is this flash fragment.
+    pub fn into_response_with(self, status: StatusCode) -> Response {
+        let fallback = self.message.clone().unwrap_or_default();
+        match self.render_html() {
+            Ok(html) => (status, html).into_response(),
+            Err(e) => {
+                tracing::error!(error = %e, "failed to render flash");
+                let mut res = (status, fallback).into_response();
+                res.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain; charset=utf-8"));
+                res
+            }
+        }
+    }
+}
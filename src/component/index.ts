@@ -2,3 +2,8 @@ require('./app/app');
 require('./joy_form/joy_form');
 require('./joy_card/joy_card');
 require('./joy_cards/joy_cards');
+require('./admin/admin');
+require('./flash/flash');
+require('./timeline/timeline');
+require('./location_picker/location_picker');
+require('./settings/settings');
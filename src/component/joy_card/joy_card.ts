@@ -1,22 +1,23 @@
 import {Component} from "../component";
 import {computed} from "@engine/signals";
 import {DateHelper} from "../../service/date";
-import {DistanceHelper} from "../../service/distance";
 
 export class JoyCard extends Component {
     protected signals = {
         joy: '',
         created: 0,
         distance: null,
+        locale: 'en-US',
+        editing: false,
+        reactions: {smile: 0, relate: 0, hug: 0},
+        reaction: null,
         createdDate: computed(() => {
             return new Date(this.signals.created);
         }),
         createdFormatted: DateHelper.computedFormat(
             () => this.signals.createdDate,
+            () => this.signals.locale,
         ),
-        distanceFormatted: computed(() => {
-            return DistanceHelper.format(this.signals.distance);
-        })
     }
 }
 window.customElements.define('app-joy-card', JoyCard);
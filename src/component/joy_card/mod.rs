@@ -1,8 +1,13 @@
-use crate::component::Renderable;
-use crate::service::joy::Joy;
+use crate::component::Component;
+use crate::service::joy::{Joy, Reaction};
+use crate::service::locale::Locale;
+use crate::service::preferences::Preferences;
+use crate::service::{date, distance};
 use crate::service::state::AppState;
+use crate::service::user::User;
 use askama::Template;
-use axum::response::Html;
+use axum::http::StatusCode;
+use serde::Deserialize;
 use uuid::Uuid;
 
 #[derive(Template)]
@@ -10,39 +15,63 @@ use uuid::Uuid;
 pub struct JoyCard {
     pub joy: Joy,
     pub created: String,
+    pub created_formatted: String,
+    pub distance_formatted: String,
+    pub locale: Locale,
+    pub owned: bool,
+    pub editable: bool,
+    pub reactions: &'static [Reaction],
+}
+
+#[derive(Deserialize)]
+pub struct JoyCardProps {
+    pub id: Uuid,
 }
 
 impl JoyCard {
-    pub async fn render_with_state_id(
-        state: &AppState,
-        id: Uuid,
-        user_id: Uuid,
-    ) -> Result<Html<String>, String> {
-        let maybe_joy = state.joys.get_for_user(id, user_id).await;
-
-        let joy = match maybe_joy {
-            Ok(Some(j)) => j,
-            Ok(None) => return Err(format!("Joy not found: {}", id)),
-            Err(e) => return Err(e),
-        };
+    pub fn dom_id(id: &Uuid) -> String {
+        format!("joy-card-{}", id)
+    }
 
+    /// An already-loaded joy as seen by `user_id`, with their preferences.
+    pub fn new(joy: Joy, user_id: Uuid, locale: Locale, preferences: &Preferences) -> Self {
         let created = joy.created
             .format(&time::format_description::well_known::Rfc3339)
             .unwrap_or_else(|_| "Invalid date".to_string());
+        let created_formatted = date::format(joy.created, preferences.now(), locale);
+        let distance_formatted = distance::format(joy.distance, preferences.units_for(locale), locale);
+
+        let owned = joy.user_id == Some(user_id);
+        let editable = owned && joy.is_editable();
 
-        let html = JoyCard {
+        JoyCard {
             joy,
             created,
+            created_formatted,
+            distance_formatted,
+            locale,
+            owned,
+            editable,
+            reactions: &Reaction::ALL,
         }
-            .render()
-            .map_err(|e| e.to_string())?;
-        Ok(Html(html))
     }
 }
 
 #[async_trait::async_trait]
-impl Renderable for JoyCard {
-    async fn render_with_state(_state: &AppState) -> Result<Html<String>, String> {
-        Err("JoyCard requires an id; use render_with_state_id".to_string())
+impl Component for JoyCard {
+    const NAME: &'static str = "joy-card";
+    type Props = JoyCardProps;
+
+    async fn build(state: &AppState, user: &User, locale: Locale, props: JoyCardProps) -> Result<Self, (StatusCode, String)> {
+        let joy = state.joys.get_for_user(props.id, user.id).await
+            .map_err(crate::service::internal_error)?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "joy not found".to_string()))?;
+        let preferences = state.preferences.get(&user.id).await
+            .map_err(crate::service::internal_error)?;
+        Ok(Self::new(joy, user.id, locale, &preferences))
+    }
+
+    fn id(&self) -> String {
+        Self::dom_id(&self.joy.id)
     }
-}
\ No newline at end of file
+}
@@ -1,12 +1,12 @@
 use crate::component::joy_card::JoyCard;
+use crate::component::{Component, NoProps};
+use crate::service::joy::Page;
+use crate::service::locale::Locale;
 use crate::service::state::AppState;
+use crate::service::user::User;
 use askama::Template;
 use axum::http::StatusCode;
 use axum::response::Html;
-use axum::routing::get;
-use axum::Router;
-use futures_util::future::join_all;
-use uuid::Uuid;
 
 #[derive(Template)]
 #[template(path = "component/joy_cards/joy_cards.html")]
@@ -14,38 +14,23 @@ pub struct JoyCards {
     joy_cards: String,
 }
 
-pub async fn render_for_user(state: &AppState, user_id: Uuid) -> Result<Html<String>, String> {
-    let joys = state.joys.list_for_user(&user_id).await?;
-
-    let futures = joys.iter().map(|j| {
-        let state = state; // capture
-        let id = j.id;
-        async move {
-            JoyCard::render_with_state_id(state, id, user_id).await.map(|h| h.0)
-        }
-    });
-
-    let results: Vec<Result<String, String>> = join_all(futures).await;
-
-    let pieces: Result<Vec<String>, String> = results.into_iter().collect();
-    let joined = pieces?.join("");
-
-    let html = JoyCards { joy_cards: joined }
-        .render()
-        .map_err(|e| e.to_string())?;
-    Ok(Html(html))
-}
-
-async fn show(state: axum::extract::State<AppState>) -> Result<Html<String>, (StatusCode, String)> {
-    // This route should not be used without session-bound user, keep old behavior disabled
-    Err((StatusCode::UNAUTHORIZED, "session required".into()))
-}
-
-pub fn router() -> Router<AppState> {
-    Router::new()
-        .nest(
-            "/joy-cards",
-            Router::new()
-                .route("/", get(show))
-        )
+#[async_trait::async_trait]
+impl Component for JoyCards {
+    const NAME: &'static str = "joy-cards";
+    type Props = NoProps;
+
+    async fn build(state: &AppState, user: &User, locale: Locale, _props: NoProps) -> Result<Self, (StatusCode, String)> {
+        let preferences = state.preferences.get(&user.id).await
+            .map_err(crate::service::internal_error)?;
+        let joys = state.joys.list_for_user(&user.id, &preferences, Page::default()).await
+            .map_err(crate::service::internal_error)?;
+
+        let pieces: Result<Vec<String>, String> = joys
+            .into_iter()
+            .map(|joy| JoyCard::new(joy, user.id, locale, &preferences).fragment().map(|Html(html)| html))
+            .collect();
+        let joy_cards = pieces.map_err(crate::service::internal_error)?.join("");
+
+        Ok(JoyCards { joy_cards })
+    }
 }
@@ -1,98 +1,200 @@
 use askama::Template;
-use axum::extract::{State};
+use axum::extract::State;
 use axum::Json;
-use axum::http::StatusCode;
-use axum::response::Html;
-use axum::routing::{get, post};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::{post, put};
 use axum::Router;
 use serde::Deserialize;
+use crate::component::app::App;
+use crate::component::flash::Flash;
+use crate::component::{is_datastar, Component, NoProps, Shell, Submitted};
+use crate::service::auth::CurrentUser;
+use crate::service::draft::{Draft, Step};
+use crate::service::joy::{self, DraftLocation};
+use crate::service::locale::Locale;
 use crate::service::state::AppState;
-use tower_sessions::Session;
+use crate::service::user::User;
+
+/// Characters each answer needs before the form moves on.
+pub const MAX_LENGTH: usize = 100;
 
+/// The joy form at whatever step the user's draft has reached. Each step is
+/// a real `<form>`, so it also works as a plain post without JavaScript.
 #[derive(Template)]
 #[template(path = "component/joy_form/joy_form.html")]
 pub struct JoyForm {
-    pub user: User,
+    pub locale: Locale,
+    pub draft: Draft,
+    pub max_length: usize,
+    pub csrf: String,
 }
 
-use crate::service::user::User;
-
-pub async fn render_for_session(state: &AppState, session: Session) -> Result<Html<String>, String> {
-    let user = state.users.get_or_create_session_user(session).await?;
-    let html = JoyForm { user }
-        .render()
-        .map_err(|e| e.to_string())?;
-    Ok(Html(html))
+impl JoyForm {
+    fn is(&self, step: Step) -> bool {
+        self.draft.step == step
+    }
 }
 
-pub async fn show(State(state): State<AppState>, session: Session) -> Result<Html<String>, (StatusCode, String)> {
-    let user = state.users.get_or_create_session_user(session).await.map_err(crate::service::internal_error)?;
+#[async_trait::async_trait]
+impl Component for JoyForm {
+    const NAME: &'static str = "joy-form";
+    type Props = NoProps;
 
-    let tpl = JoyForm { user };
-    let html = tpl.render().map_err(crate::service::internal_error)?;
-    Ok(Html(html))
+    async fn build(state: &AppState, user: &User, locale: Locale, _props: NoProps) -> Result<Self, (StatusCode, String)> {
+        let draft = state.drafts.get(&user.id).await
+            .map_err(crate::service::internal_error)?;
+        Ok(JoyForm { locale, draft, max_length: MAX_LENGTH, csrf: user.csrf.clone() })
+    }
 }
 
+/// The form's fields. Datastar sends every signal; a plain form post only
+/// the answer on its step, so absent answers leave the draft's alone.
 #[derive(Deserialize)]
-pub struct NewJoy {
-    frustration: String,
-    context: String,
-    joy: String,
+pub struct Submission {
+    frustration: Option<String>,
+    context: Option<String>,
+    joy: Option<String>,
     longitude: Option<f64>,
     latitude: Option<f64>,
+    accuracy: Option<f64>,
 }
 
-pub async fn create(
+impl Submission {
+    fn apply(self, draft: &mut Draft) {
+        let answers = &mut draft.answers;
+        for (answer, value) in [
+            (&mut answers.frustration, self.frustration),
+            (&mut answers.context, self.context),
+            (&mut answers.joy, self.joy),
+        ] {
+            if let Some(value) = value {
+                *answer = value;
+            }
+        }
+    }
+}
+
+/// Whether the answer on the current step is long enough to move on.
+fn is_complete(draft: &Draft) -> bool {
+    draft.current_answer().is_none_or(|answer| answer.chars().count() >= MAX_LENGTH)
+}
+
+/// Answers an action on the form: Datastar gets the re-rendered form to
+/// patch in, a plain post is redirected back to the app, or shown the app
+/// with the error if the action failed.
+async fn respond(
+    state: &AppState,
+    user: &User,
+    shell: &Shell,
+    headers: &HeaderMap,
+    outcome: Result<(), (StatusCode, String)>,
+) -> Result<Response, (StatusCode, String)> {
+    if is_datastar(headers) {
+        outcome?;
+        let html = JoyForm::build(state, user, shell.locale, NoProps::default())
+            .await?
+            .fragment()
+            .map_err(crate::service::internal_error)?;
+        return Ok(html.into_response());
+    }
+
+    match outcome {
+        Ok(()) => Ok(Redirect::to("/").into_response()),
+        Err((status, message)) if status.is_client_error() => {
+            let page = App::build(state, user, shell.locale, NoProps::default())
+                .await?
+                .with_flash(Flash::error(message))
+                .and_then(|app| app.page(shell))
+                .map_err(crate::service::internal_error)?;
+            Ok((status, page).into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Autosaves the answers as they are typed. Once a question before the
+/// last has been answered in full the form moves on by itself.
+pub async fn autosave(
     State(state): State<AppState>,
-    session: Session,
-    Json(form): Json<NewJoy>,
-) -> Result<Html<String>, (StatusCode, String)> {
-    let user = state
-        .users
-        .get_or_create_session_user(session)
-        .await
+    CurrentUser(user): CurrentUser,
+    shell: Shell,
+    headers: HeaderMap,
+    Json(form): Json<Submission>,
+) -> Result<Response, (StatusCode, String)> {
+    let mut draft = state.drafts.get(&user.id).await
         .map_err(crate::service::internal_error)?;
+    form.apply(&mut draft);
 
-    if let (Some(lon), Some(lat)) = (form.longitude, form.latitude) {
-        // persist the last known location for this user
-        if let Err(e) = state.users.update_location(&user.id, lat, lon).await {
-            tracing::warn!(error = %e, "failed to update user location");
-        }
+    let advance = matches!(draft.step, Step::Frustration | Step::Context) && is_complete(&draft);
+    if advance {
+        draft.step = draft.step.next();
     }
+    state.drafts.save(&user.id, &draft).await
+        .map_err(crate::service::internal_error)?;
+
+    if advance {
+        respond(&state, &user, &shell, &headers, Ok(())).await
+    } else {
+        Ok(StatusCode::NO_CONTENT.into_response())
+    }
+}
 
-    let res = state
-        .joys
-        .create(
-            &user.id,
-            form.frustration.clone(),
-            form.context.clone(),
-            form.joy.clone(),
-        )
-        .await;
-
-    if let Err(err) = res {
-        return Err((StatusCode::BAD_REQUEST, err));
+/// Saves the answers and moves to the next step, if the current answer is
+/// long enough.
+pub async fn next(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    shell: Shell,
+    headers: HeaderMap,
+    Submitted(form): Submitted<Submission>,
+) -> Result<Response, (StatusCode, String)> {
+    let mut draft = state.drafts.get(&user.id).await
+        .map_err(crate::service::internal_error)?;
+    form.apply(&mut draft);
+    if is_complete(&draft) {
+        draft.step = draft.step.next();
     }
+    state.drafts.save(&user.id, &draft).await
+        .map_err(crate::service::internal_error)?;
 
-    let form = JoyForm { user: user.clone() }
-        .render()
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    respond(&state, &user, &shell, &headers, Ok(())).await
+}
 
-    let Html(cards) = crate::component::joy_cards::render_for_user(&state, user.id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+/// Posts the draft, with the answers brought up to date in case the last
+/// keystrokes were not autosaved yet. A draft that has not reached the last
+/// step with every question answered is sent back instead.
+pub async fn create(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    shell: Shell,
+    headers: HeaderMap,
+    Submitted(form): Submitted<Submission>,
+) -> Result<Response, (StatusCode, String)> {
+    let mut draft = state.drafts.get(&user.id).await
+        .map_err(crate::service::internal_error)?;
+    let location = DraftLocation {
+        longitude: form.longitude,
+        latitude: form.latitude,
+        accuracy: form.accuracy,
+    };
+    form.apply(&mut draft);
+    state.drafts.update_answers(&user.id, &draft.answers).await
+        .map_err(crate::service::internal_error)?;
 
-    if let Err(e) = state.sse.publish_html(cards) {
-        tracing::warn!(?e, "failed to publish SSE html");
+    if !draft.is_ready() {
+        let outcome = Err((StatusCode::BAD_REQUEST, "answer every question before posting".to_string()));
+        return respond(&state, &user, &shell, &headers, outcome).await;
     }
 
-    Ok(Html(form))
+    // The draft is gone afterwards, so the form starts over for the next joy.
+    let outcome = joy::submit_draft(&state, &user, location).await.map(|_| ());
+    respond(&state, &user, &shell, &headers, outcome).await
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new()
-        .nest(
-            "/joy-form",
-            Router::new().route("/", get(show)).route("/", post(create)),
-        )
+    JoyForm::router()
+        .route("/joy-form", post(create))
+        .route("/joy-form/draft", put(autosave))
+        .route("/joy-form/next", post(next))
 }
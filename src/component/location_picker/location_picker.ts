@@ -0,0 +1,12 @@
+import {Component} from "../component";
+
+export class LocationPicker extends Component {
+    protected signals = {
+        placeQuery: '',
+    };
+}
+window.customElements.define('app-location-picker', LocationPicker);
+
+export class PlaceResults extends Component {
+}
+window.customElements.define('app-place-results', PlaceResults);
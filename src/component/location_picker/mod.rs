@@ -0,0 +1,141 @@
+use askama::Template;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Html;
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Deserialize;
+
+use crate::component::{Component, NoProps};
+use crate::service::auth::CurrentUser;
+use crate::service::gazetteer::Place;
+use crate::service::locale::Locale;
+use crate::service::sse::Patch;
+use crate::service::state::AppState;
+use crate::service::user::User;
+
+const SEARCH_LIMIT: usize = 8;
+
+/// Lets users without geolocation pick where they are from the gazetteer.
+#[derive(Template)]
+#[template(path = "component/location_picker/location_picker.html")]
+pub struct LocationPicker {
+    pub locale: Locale,
+    pub current: Option<String>,
+    pub located: bool,
+    pub results: String,
+}
+
+#[derive(Template)]
+#[template(path = "component/location_picker/place_results.html")]
+pub struct PlaceResults {
+    pub locale: Locale,
+    pub query: String,
+    pub places: Vec<Place>,
+}
+
+impl PlaceResults {
+    fn render_html(self) -> Result<Html<String>, String> {
+        self.render().map(Html).map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait::async_trait]
+impl Component for LocationPicker {
+    const NAME: &'static str = "location-picker";
+    type Props = NoProps;
+
+    async fn build(state: &AppState, user: &User, locale: Locale, _props: NoProps) -> Result<Self, (StatusCode, String)> {
+        let location = user.latitude.zip(user.longitude);
+        let current = location.map(|(lat, lon)| {
+            state.places
+                .label(lat, lon)
+                .map(|label| label.localised(&locale))
+                .unwrap_or_else(|| format!("{:.2}, {:.2}", lat, lon))
+        });
+
+        let Html(results) = PlaceResults { locale, query: String::new(), places: Vec::new() }
+            .render_html()
+            .map_err(crate::service::internal_error)?;
+        Ok(LocationPicker {
+            locale,
+            current,
+            located: location.is_some(),
+            results,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    datastar: Option<String>,
+    q: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct SearchSignals {
+    #[serde(default)]
+    place_query: String,
+}
+
+pub async fn search(
+    State(state): State<AppState>,
+    locale: Locale,
+    Query(query): Query<SearchQuery>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let signals: SearchSignals = match query.datastar {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?,
+        None => SearchSignals::default(),
+    };
+    let query = query.q.unwrap_or(signals.place_query);
+
+    let places = state.places
+        .search(&query, SEARCH_LIMIT)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    PlaceResults { locale, query, places }
+        .render_html()
+        .map_err(crate::service::internal_error)
+}
+
+pub async fn choose(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    locale: Locale,
+    Path(id): Path<usize>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let place = state.places
+        .get(id)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "place not found".to_string()))?;
+
+    state.users.set_manual_location(&user.id, place.longitude, place.latitude).await
+        .map_err(crate::service::internal_error)?;
+
+    if let Err(e) = state.sse.publish(Patch::JoyCards { user_id: Some(user.id) }) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    let user = User {
+        latitude: Some(place.latitude),
+        longitude: Some(place.longitude),
+        ..user
+    };
+    LocationPicker::build(&state, &user, locale, NoProps::default())
+        .await?
+        .fragment()
+        .map_err(crate::service::internal_error)
+}
+
+pub fn router() -> Router<AppState> {
+    LocationPicker::router()
+        .nest(
+            "/places",
+            Router::new()
+                .route("/", get(search))
+                .route("/:id", post(choose)),
+        )
+}
@@ -1,13 +1,145 @@
+use askama::Template;
+use axum::extract::{FromRequest, FromRequestParts, Query, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::Event;
 use axum::response::Html;
+use axum::routing::get;
+use axum::{Form, Json, Router};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 
+use crate::service::auth::CurrentUser;
+use crate::service::locale::Locale;
+use crate::service::security::CspNonce;
+use crate::service::sse;
 use crate::service::state::AppState;
+use crate::service::user::User;
 
+pub mod admin;
 pub mod app;
+pub mod flash;
 pub mod joy_form;
 pub mod joy_cards;
 pub mod joy_card;
+pub mod location_picker;
+pub mod registry;
+pub mod settings;
+pub mod timeline;
+
+/// Props for components that need nothing beyond the viewer.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NoProps {}
+
+/// A server-rendered custom element. The template's root element is
+/// `<app-{NAME} id="{{ self.id() }}">`, which is what Datastar patches by.
+#[async_trait::async_trait]
+pub trait Component: Template + Sized + Send + 'static {
+    /// Route segment of the fragment route, `GET /{NAME}`.
+    const NAME: &'static str;
+
+    /// What the component needs besides the viewer. The fragment route
+    /// reads it from the query string.
+    type Props: DeserializeOwned + Send + 'static;
+
+    /// Loads the component as seen by `user`. Fails with 404 if they cannot
+    /// see it, or 403 if they may not.
+    async fn build(state: &AppState, user: &User, locale: Locale, props: Self::Props) -> Result<Self, (StatusCode, String)>;
+
+    /// The stable DOM id of the root element.
+    fn id(&self) -> String {
+        Self::NAME.to_string()
+    }
+
+    fn fragment(&self) -> Result<Html<String>, String> {
+        self.render().map(Html).map_err(|e| e.to_string())
+    }
+
+    /// The component as the whole body of the app shell.
+    fn page(&self, shell: &Shell) -> Result<Html<String>, String> {
+        let Html(app) = self.fragment()?;
+        crate::Index { lang: shell.locale.tag(), csrf: &shell.csrf, nonce: &shell.nonce, app }
+            .render()
+            .map(Html)
+            .map_err(|e| e.to_string())
+    }
+
+    /// An SSE event replacing the element with this id.
+    fn patch(&self) -> Result<Event, String> {
+        self.fragment().map(|Html(html)| sse::elements_event(&html))
+    }
+
+    /// `GET /{NAME}`: a fragment for Datastar requests, otherwise a full
+    /// page, so every component is also linkable. Components add their own
+    /// actions to this router.
+    fn router() -> Router<AppState> {
+        Router::new().route(&format!("/{}", Self::NAME), get(serve::<Self>))
+    }
+}
+
+async fn serve<C: Component>(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    shell: Shell,
+    headers: HeaderMap,
+    Query(props): Query<C::Props>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let component = C::build(&state, &user, shell.locale, props).await?;
+    let html = if is_datastar(&headers) {
+        component.fragment()
+    } else {
+        component.page(&shell)
+    };
+    html.map_err(crate::service::internal_error)
+}
+
+/// What the page around a component needs: the viewer's language and CSRF
+/// token, and the response's CSP nonce.
+pub struct Shell {
+    pub locale: Locale,
+    pub csrf: String,
+    pub nonce: String,
+}
+
+#[async_trait::async_trait]
+impl FromRequestParts<AppState> for Shell {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let CurrentUser(user) = CurrentUser::from_request_parts(parts, state).await?;
+        let locale = Locale::from_request_parts(parts, state).await?;
+        let Ok(CspNonce(nonce)) = CspNonce::from_request_parts(parts, state).await;
+        Ok(Shell { locale, csrf: user.csrf.clone(), nonce })
+    }
+}
+
+/// Whether Datastar made the request, and so wants a fragment to patch in
+/// rather than a page to navigate to.
+pub fn is_datastar(headers: &HeaderMap) -> bool {
+    headers.contains_key("datastar-request")
+}
+
+/// A body sent either by Datastar as JSON signals or by a plain HTML form
+/// without JavaScript. Both have passed the CSRF middleware already.
+pub struct Submitted<T>(pub T);
 
 #[async_trait::async_trait]
-pub trait Renderable {
-    async fn render_with_state(state: &AppState) -> Result<Html<String>, String>;
+impl<T: DeserializeOwned> FromRequest<AppState> for Submitted<T> {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let form_encoded = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+        if form_encoded {
+            let Form(value) = Form::<T>::from_request(req, state).await.map_err(|e| (e.status(), e.body_text()))?;
+            Ok(Submitted(value))
+        } else {
+            let Json(value) = Json::<T>::from_request(req, state).await.map_err(|e| (e.status(), e.body_text()))?;
+            Ok(Submitted(value))
+        }
+    }
 }
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use axum::Router;
+
+use crate::component::admin::{self, Admin};
+use crate::component::app::App;
+use crate::component::joy_card::JoyCard;
+use crate::component::joy_cards::JoyCards;
+use crate::component::joy_form::{self, JoyForm};
+use crate::component::location_picker::{self, LocationPicker};
+use crate::component::settings::{self, Settings};
+use crate::component::timeline::{self, Timeline};
+use crate::component::Component;
+use crate::service::state::AppState;
+
+/// A component as mounted by the app. It owns the top-level path segment
+/// `/{name}` and any extra segments it `claims`.
+pub struct Registration {
+    pub name: &'static str,
+    /// Short type name of the props read from the fragment route's query.
+    pub props: &'static str,
+    claims: &'static [&'static str],
+    router: fn() -> Router<AppState>,
+}
+
+impl Registration {
+    fn of<C: Component>() -> Self {
+        let props = std::any::type_name::<C::Props>();
+        Self {
+            name: C::NAME,
+            props: props.rsplit("::").next().unwrap_or(props),
+            claims: &[],
+            router: C::router,
+        }
+    }
+
+    /// Replaces the bare fragment route with the component's own router,
+    /// which adds its actions to it.
+    fn routes(self, router: fn() -> Router<AppState>) -> Self {
+        Self { router, ..self }
+    }
+
+    /// Top-level segments the component's actions use besides its name.
+    fn claims(self, claims: &'static [&'static str]) -> Self {
+        Self { claims, ..self }
+    }
+
+    pub fn fragment_route(&self) -> String {
+        format!("/{}", self.name)
+    }
+
+    fn segments(&self) -> impl Iterator<Item = &'static str> + '_ {
+        std::iter::once(self.name).chain(self.claims.iter().copied())
+    }
+}
+
+/// Every component the app serves. New components are added here.
+pub fn components() -> Vec<Registration> {
+    vec![
+        Registration::of::<App>(),
+        Registration::of::<Admin>().routes(admin::router),
+        Registration::of::<JoyForm>().routes(joy_form::router),
+        Registration::of::<JoyCards>(),
+        Registration::of::<JoyCard>(),
+        Registration::of::<LocationPicker>().routes(location_picker::router).claims(&["places"]),
+        Registration::of::<Timeline>().routes(timeline::router),
+        Registration::of::<Settings>().routes(settings::router),
+    ]
+}
+
+/// Merges the routers of all registered components. `reserved` lists the
+/// top-level segments the app routes itself; a component claiming one of
+/// those, or one already claimed by another component, is an error rather
+/// than a panic deep inside axum or a silently shadowed route.
+pub fn router(reserved: &[&'static str]) -> Result<Router<AppState>, String> {
+    let components = components();
+
+    let mut owners: HashMap<&str, &str> = reserved.iter().map(|segment| (*segment, "the app")).collect();
+    for component in &components {
+        for segment in component.segments() {
+            if let Some(owner) = owners.insert(segment, component.name) {
+                return Err(format!(
+                    "component {} claims /{}, which is already routed by {}",
+                    component.name, segment, owner
+                ));
+            }
+        }
+    }
+
+    Ok(components
+        .iter()
+        .fold(Router::new(), |router, component| router.merge((component.router)())))
+}
+
+/// One line per fragment route, for the startup log and `/_components`.
+pub fn describe() -> String {
+    components()
+        .iter()
+        .map(|component| format!("GET {} ({})\n", component.fragment_route(), component.props))
+        .collect()
+}
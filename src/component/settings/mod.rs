@@ -0,0 +1,226 @@
+use askama::Template;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, Redirect};
+use axum::routing::post;
+use axum::{Form, Router};
+use serde::Deserialize;
+use time::{Duration, OffsetDateTime};
+use tower_sessions::Session;
+use uuid::Uuid;
+
+use crate::component::{Component, Shell};
+use crate::service::auth::{CurrentUser, RequireAccount};
+use crate::service::date;
+use crate::service::i18n::set_session_locale;
+use crate::service::locale::{Locale, Units};
+use crate::service::preferences::{Preferences, Ranking, RADIUS_CHOICES};
+use crate::service::sse::Patch;
+use crate::service::state::AppState;
+use crate::service::token::{ApiToken, Scope};
+use crate::service::user::User;
+
+/// Offered token lifetimes in days; 0 means the token never expires.
+const TOKEN_LIFETIMES: [u32; 4] = [30, 90, 365, 0];
+
+#[derive(Template)]
+#[template(path = "component/settings/settings.html")]
+pub struct Settings {
+    pub locale: Locale,
+    pub preferences: Preferences,
+    pub saved: bool,
+    pub locales: &'static [Locale],
+    pub units: &'static [Units],
+    pub radii: &'static [u32],
+    pub rankings: &'static [Ranking],
+    pub tokens: Vec<ApiToken>,
+    /// The secret of a token just created, shown this once.
+    pub new_token: Option<String>,
+    pub token_error: Option<String>,
+    pub scopes: &'static [Scope],
+    pub lifetimes: &'static [u32],
+    pub csrf: String,
+}
+
+impl Settings {
+    fn new(locale: Locale, preferences: Preferences, tokens: Vec<ApiToken>, csrf: String) -> Self {
+        Self {
+            locale,
+            preferences,
+            saved: false,
+            locales: &Locale::ALL,
+            units: &Units::ALL,
+            radii: &RADIUS_CHOICES,
+            rankings: &Ranking::ALL,
+            tokens,
+            new_token: None,
+            token_error: None,
+            scopes: &Scope::ALL,
+            lifetimes: &TOKEN_LIFETIMES,
+            csrf,
+        }
+    }
+
+    fn is_locale(&self, locale: &Locale) -> bool {
+        self.preferences.locale == Some(*locale)
+    }
+
+    fn is_units(&self, units: &Units) -> bool {
+        self.preferences.units == Some(*units)
+    }
+
+    fn is_radius(&self, km: &u32) -> bool {
+        (self.preferences.radius_km() - *km as f64).abs() < 0.5
+    }
+
+    fn radius_label(&self, km: &u32) -> String {
+        self.locale.t_count("settings-radius-option", *km)
+    }
+
+    fn is_ranking(&self, ranking: &Ranking) -> bool {
+        self.preferences.ranking == *ranking
+    }
+
+    fn day(&self, date: &OffsetDateTime) -> String {
+        date::format_day(date.to_offset(self.preferences.now().offset()), self.locale)
+    }
+
+    fn token_scopes(&self, token: &ApiToken) -> String {
+        token.scopes
+            .iter()
+            .map(|scope| self.locale.t(scope.label_id()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn lifetime_label(&self, days: &u32) -> String {
+        match days {
+            0 => self.locale.t("settings-token-never"),
+            days => self.locale.t_count("settings-token-days", *days),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub struct SettingsProps {
+    /// Set by the redirect after a save, to show the confirmation.
+    #[serde(default)]
+    saved: bool,
+}
+
+#[async_trait::async_trait]
+impl Component for Settings {
+    const NAME: &'static str = "settings";
+    type Props = SettingsProps;
+
+    async fn build(state: &AppState, user: &User, locale: Locale, props: SettingsProps) -> Result<Self, (StatusCode, String)> {
+        let preferences = state.preferences.get(&user.id).await
+            .map_err(crate::service::internal_error)?;
+        let tokens = state.tokens.list(&user.id).await
+            .map_err(crate::service::internal_error)?;
+        Ok(Settings { saved: props.saved, ..Settings::new(locale, preferences, tokens, user.csrf.clone()) })
+    }
+}
+
+/// A plain HTML form post, so a language change re-renders the whole page.
+#[derive(Deserialize)]
+pub struct SettingsForm {
+    units: String,
+    locale: String,
+    radius: u32,
+    ranking: String,
+    share_location: Option<String>,
+}
+
+pub async fn save(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    session: Session,
+    Form(form): Form<SettingsForm>,
+) -> Result<Redirect, (StatusCode, String)> {
+    let ranking = Ranking::parse(&form.ranking)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("unknown ranking: {}", form.ranking)))?;
+    if !RADIUS_CHOICES.contains(&form.radius) {
+        return Err((StatusCode::BAD_REQUEST, format!("unsupported radius: {}", form.radius)));
+    }
+
+    let previous = state.preferences.get(&user.id).await
+        .map_err(crate::service::internal_error)?;
+    let preferences = Preferences {
+        units: Units::parse(&form.units),
+        locale: Locale::parse(&form.locale),
+        radius: form.radius as f64 * 1000.0,
+        ranking,
+        share_location: form.share_location.is_some(),
+        utc_offset: previous.utc_offset,
+    };
+
+    set_session_locale(&session, preferences.locale).await
+        .map_err(crate::service::internal_error)?;
+    state.preferences.save(&user.id, &preferences).await
+        .map_err(crate::service::internal_error)?;
+
+    if previous.share_location && !preferences.share_location {
+        state.users.forget_location(&user.id).await
+            .map_err(crate::service::internal_error)?;
+    }
+
+    if let Err(e) = state.sse.publish(Patch::JoyCards { user_id: Some(user.id) }) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(Redirect::to("/settings?saved=true"))
+}
+
+#[derive(Deserialize)]
+pub struct TokenForm {
+    name: String,
+    read: Option<String>,
+    write: Option<String>,
+    lifetime: u32,
+}
+
+/// Issues a personal access token and shows its secret on the settings
+/// page, the only time it is ever displayed.
+pub async fn create_token(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    shell: Shell,
+    Form(form): Form<TokenForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    if !TOKEN_LIFETIMES.contains(&form.lifetime) {
+        return Err((StatusCode::BAD_REQUEST, format!("unsupported lifetime: {}", form.lifetime)));
+    }
+    let scopes: Vec<Scope> = [(Scope::Read, &form.read), (Scope::Write, &form.write)]
+        .into_iter()
+        .filter_map(|(scope, checked)| checked.as_ref().map(|_| scope))
+        .collect();
+    let lifetime = (form.lifetime > 0).then(|| Duration::days(form.lifetime as i64));
+
+    let created = state.tokens.create(&user.id, &form.name, &scopes, lifetime).await;
+
+    let settings = Settings::build(&state, &user, shell.locale, SettingsProps::default()).await?;
+    let settings = match created {
+        Ok((_, secret)) => Settings { new_token: Some(secret), ..settings },
+        Err(e) => Settings { token_error: Some(e), ..settings },
+    };
+    settings.page(&shell).map_err(crate::service::internal_error)
+}
+
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Path(id): Path<Uuid>,
+) -> Result<Redirect, (StatusCode, String)> {
+    if !state.tokens.revoke(&id, &user.id).await.map_err(crate::service::internal_error)? {
+        return Err((StatusCode::NOT_FOUND, "token not found".to_string()));
+    }
+    Ok(Redirect::to("/settings"))
+}
+
+pub fn router() -> Router<AppState> {
+    Settings::router()
+        .route("/settings", post(save))
+        .route("/settings/tokens", post(create_token))
+        .route("/settings/tokens/:id/revoke", post(revoke_token))
+}
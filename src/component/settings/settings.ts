@@ -0,0 +1,5 @@
+import {Component} from "../component";
+
+export class Settings extends Component {
+}
+window.customElements.define('app-settings', Settings);
@@ -0,0 +1,130 @@
+use askama::Template;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use axum::response::Html;
+use axum::routing::post;
+use axum::Router;
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::component::{Component, NoProps};
+use crate::service::auth::CurrentUser;
+use crate::service::date;
+use crate::service::gazetteer::PlaceLabel;
+use crate::service::locale::Locale;
+use crate::service::state::AppState;
+use crate::service::user::User;
+
+const TIMELINE_LIMIT: i64 = 200;
+
+pub struct TimelineEntry {
+    pub created: OffsetDateTime,
+    pub joy: Option<String>,
+    pub coordinates: Option<String>,
+    pub accuracy: Option<String>,
+    pub place: Option<PlaceLabel>,
+}
+
+impl TimelineEntry {
+    fn new(
+        created: OffsetDateTime,
+        joy: Option<String>,
+        longitude: Option<f64>,
+        latitude: Option<f64>,
+        accuracy: Option<f64>,
+        place: Option<PlaceLabel>,
+    ) -> Self {
+        Self {
+            created,
+            joy,
+            coordinates: longitude
+                .zip(latitude)
+                .map(|(lon, lat)| format!("{:.4}, {:.4}", lat, lon)),
+            accuracy: accuracy.map(|a| format!("±{:.0} m", a)),
+            place,
+        }
+    }
+
+    pub fn datetime(&self) -> String {
+        self.created
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_else(|_| "Invalid date".to_string())
+    }
+
+    pub fn when(&self, locale: &Locale, now: &OffsetDateTime) -> String {
+        date::format(self.created, *now, *locale)
+    }
+}
+
+/// "Where I wrote my joys": the user's own joys, interleaved with their
+/// movement history if they have opted in to keeping one.
+#[derive(Template)]
+#[template(path = "component/timeline/timeline.html")]
+pub struct Timeline {
+    pub locale: Locale,
+    pub user: User,
+    pub entries: Vec<TimelineEntry>,
+    /// Now on the user's clock, so times render in their time zone.
+    pub now: OffsetDateTime,
+}
+
+#[async_trait::async_trait]
+impl Component for Timeline {
+    const NAME: &'static str = "timeline";
+    type Props = NoProps;
+
+    async fn build(state: &AppState, user: &User, locale: Locale, _props: NoProps) -> Result<Self, (StatusCode, String)> {
+        let joys = state.joys.list_own(&user.id, TIMELINE_LIMIT).await
+            .map_err(crate::service::internal_error)?;
+        let fixes = if user.location_history {
+            state.users.location_history(&user.id, TIMELINE_LIMIT).await
+                .map_err(crate::service::internal_error)?
+        } else {
+            Vec::new()
+        };
+
+        let mut entries: Vec<TimelineEntry> = joys
+            .into_iter()
+            .map(|j| TimelineEntry::new(j.created, Some(j.joy), j.longitude, j.latitude, j.accuracy, j.place))
+            .chain(fixes.into_iter().map(|f| {
+                let place = state.places.label(f.latitude, f.longitude);
+                TimelineEntry::new(f.created, None, Some(f.longitude), Some(f.latitude), f.accuracy, place)
+            }))
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created));
+
+        let now = state.preferences.get(&user.id).await
+            .map_err(crate::service::internal_error)?
+            .now();
+
+        Ok(Timeline { locale, user: user.clone(), entries, now })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LocationHistoryForm {
+    enabled: bool,
+}
+
+/// Turns the location history on or off and re-renders the timeline.
+pub async fn update_location_history(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    locale: Locale,
+    Json(form): Json<LocationHistoryForm>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    state.users.set_location_history(&user.id, form.enabled).await
+        .map_err(crate::service::internal_error)?;
+
+    let user = User { location_history: form.enabled, ..user };
+    Timeline::build(&state, &user, locale, NoProps::default())
+        .await?
+        .fragment()
+        .map_err(crate::service::internal_error)
+}
+
+pub fn router() -> Router<AppState> {
+    Timeline::router()
+        .route("/timeline/location-history", post(update_location_history))
+}
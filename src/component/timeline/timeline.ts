@@ -0,0 +1,8 @@
+import {Component} from "../component";
+
+export class Timeline extends Component {
+    protected signals = {
+        enabled: false,
+    };
+}
+window.customElements.define('app-timeline', Timeline);
@@ -4,3 +4,4 @@ export const DSS = lol.slice(4)
 export const DATASTAR_FETCH_EVENT = 'datastar-fetch'
 export const DATASTAR_SIGNAL_PATCH_EVENT = 'datastar-signal-patch'
 export const DATASTAR_ELEMENT_PATCH_EVENT = 'datastar-element-patch'
+export const DATASTAR_ELEMENT_SIGNALS_EVENT = 'datastar-element-signals'
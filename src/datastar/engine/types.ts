@@ -4,9 +4,18 @@ export type Store = Record<string, any>
 export type JSONPatch = Store & { length?: never }
 export type Paths = [string, any][]
 
+export type DatastarElementPatchMode = 'outer' | 'remove'
+
 export type DatastarElementPatchEvent = {
     id: string
-    element: Element
+    element: Element | null
+    mode: DatastarElementPatchMode
+}
+
+export type DatastarElementSignalsEvent = {
+    id: string
+    signals: JSONPatch
+    onlyIfMissing: boolean
 }
 
 export type DatastarFetchEvent = {
@@ -82,6 +82,11 @@ const createHttpMethod = (name: string, method: string): void =>
           if (contentType === 'json') {
             initialHeaders['Content-Type'] = 'application/json'
           }
+          // The server refuses state-changing requests without the page's token.
+          const csrf = document.querySelector<HTMLMetaElement>('meta[name="csrf-token"]')?.content
+          if (csrf) {
+            initialHeaders['X-CSRF-Token'] = csrf
+          }
           const headers = Object.assign({}, initialHeaders, userHeaders)
 
           // We ignore the content-type header if using form data
@@ -556,6 +561,7 @@ const fetchEventSource = (
             'signals',
             overrides,
             'onlyIfMissing',
+            'selector',
           )
         }
 
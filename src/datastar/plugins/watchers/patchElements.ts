@@ -3,13 +3,19 @@
 // Description: Patches elements into the DOM.
 
 import { watcher } from '@engine'
-import type {DatastarElementPatchEvent, WatcherContext} from '@engine/types'
+import type {
+  DatastarElementPatchEvent,
+  DatastarElementPatchMode,
+  WatcherContext,
+} from '@engine/types'
 import { supportsViewTransitions } from '@utils/view-transitions'
 import {getHostFor} from "@engine/signals";
 import {DATASTAR_ELEMENT_PATCH_EVENT} from "@engine/consts";
 
 type PatchElementsArgs = {
   elements: string
+  selector: string
+  mode: DatastarElementPatchMode
   useViewTransition: boolean
 }
 
@@ -17,10 +23,12 @@ watcher({
   name: 'datastar-patch-elements',
   apply(
     ctx,
-    { elements = '', useViewTransition },
+    { elements = '', selector = '', mode = 'outer', useViewTransition },
   ) {
     const args: PatchElementsArgs = {
       elements,
+      selector: selector.trim(),
+      mode: mode.trim() as DatastarElementPatchMode,
       useViewTransition: useViewTransition?.trim() === 'true',
     }
 
@@ -32,10 +40,30 @@ watcher({
   },
 })
 
+const dispatchPatch = (detail: DatastarElementPatchEvent) => {
+  document.dispatchEvent(
+      new CustomEvent<DatastarElementPatchEvent>(DATASTAR_ELEMENT_PATCH_EVENT, {
+          detail,
+          // @todo do we need these??
+          bubbles: true,
+          composed: true,
+      }),
+  )
+}
+
 const onPatchElements = (
   { el, error }: WatcherContext,
-  { elements }: PatchElementsArgs,
+  { elements, selector, mode }: PatchElementsArgs,
 ) => {
+  if (mode === 'remove') {
+    if (!selector.startsWith('#')) {
+      console.warn(error('PatchElementsInvalidSelector'), { selector })
+      return
+    }
+    dispatchPatch({ id: selector.slice(1), element: null, mode })
+    return
+  }
+
   const newDocument = new DOMParser().parseFromString(
     `<body><template>${elements}</template></body>`,
     'text/html',
@@ -51,16 +79,6 @@ const onPatchElements = (
       continue
     }
 
-    document.dispatchEvent(
-        new CustomEvent<DatastarElementPatchEvent>(DATASTAR_ELEMENT_PATCH_EVENT, {
-            detail: {
-              id: child.id,
-              element: child,
-            },
-            // @todo do we need these??
-            bubbles: true,
-            composed: true,
-        }),
-    )
+    dispatchPatch({ id: child.id, element: child, mode })
   }
 }
@@ -5,12 +5,32 @@
 import { watcher } from '@engine'
 import {root, mergePatch} from '@engine/signals'
 import { jsStrToObject } from '@utils/text'
+import {DATASTAR_ELEMENT_SIGNALS_EVENT} from "@engine/consts";
+import type {DatastarElementSignalsEvent} from "@engine/types";
 
 watcher({
   name: 'datastar-patch-signals',
-  apply({ error }, { signals, onlyIfMissing }) {
+  apply({ error }, { signals, selector, onlyIfMissing }) {
     if (signals) {
       const ifMissing = onlyIfMissing?.trim() === 'true'
+      const target = selector?.trim()
+      if (target) {
+        if (!target.startsWith('#')) {
+          throw error('PatchSignalsInvalidSelector', { selector })
+        }
+        // Component stores live on shadow hosts, so let the matching
+        // component merge the patch into its own store.
+        document.dispatchEvent(
+          new CustomEvent<DatastarElementSignalsEvent>(DATASTAR_ELEMENT_SIGNALS_EVENT, {
+            detail: {
+              id: target.slice(1),
+              signals: jsStrToObject(signals),
+              onlyIfMissing: ifMissing,
+            },
+          }),
+        )
+        return
+      }
       mergePatch(jsStrToObject(signals), root, {ifMissing})
     } else {
       throw error('PatchSignalsExpectedSignals')
@@ -3,10 +3,14 @@ require('@bundles/datastar');
 require('./component');
 
 import {LocationService} from "./service/location";
+import {TimeZoneService} from "./service/timezone";
 document.addEventListener('DOMContentLoaded', () => {
     const location = new LocationService();
+    // Only the feed needs a live location, and only if the user shares it.
+    const sharing = document.querySelector('app-app[data-share-location="true"]') !== null;
     global.service = {
         location,
-        watch: location.start(),
+        watch: sharing ? location.start() : undefined,
     };
+    new TimeZoneService().sync();
 });
@@ -1,8 +1,10 @@
-use axum::routing::post;
+use axum::routing::{delete, patch, post};
 use sqlx::postgres::PgPoolOptions;
 use {
     axum::{
         extract::State,
+        http::Method,
+        middleware,
         response::Html,
         routing::{get, get_service},
         http::StatusCode,
@@ -10,37 +12,74 @@ use {
     },
     askama::Template,
     core::error::Error,
-    std::sync::Arc,
+    std::{net::SocketAddr, sync::Arc, time::Duration},
     tower_http::services::{ServeDir, ServeFile},
     tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt},
 };
-use tower_sessions::{Session, SessionManagerLayer};
+use tower_sessions::cookie::SameSite;
+use tower_sessions::SessionManagerLayer;
 use tower_sessions_sqlx_store::PostgresStore;
 
+mod api;
 mod component;
 mod service;
 
+use component::{app::App, Component, NoProps, Shell};
+
 use service::{
+    admin::AdminService,
+    auth::CurrentUser,
+    debounce::Debouncer,
+    draft::DraftService,
     event::EventBus,
+    gazetteer::Gazetteer,
     sse::{events as sse_events, SseService},
+    tls::TlsConfig,
+    token::TokenService,
     state::AppState,
     joy::JoyService,
-    user::UserService,
+    moderation::{Blocklist, ModerationService},
+    preferences::PreferencesService,
+    privacy::PrivacyService,
+    rate_limit::{Limit, RateLimiter},
+    report::{ReportService, DEFAULT_REPORT_THRESHOLD},
+    retention::{spawn_retention, RetentionPolicy, RetentionService},
+    security::SecurityHeaders,
+    session::{spawn_cleanup, SessionPolicy},
+    user::{HistoryRetention, LocationThreshold, UserService},
+};
+use crate::service::{
+    i18n::set_locale,
+    joy::{delete_joy, react_to_joy, update_joy},
+    preferences::set_time_zone,
+    privacy::{erase_account, export_data},
+    report::{hide_author, hide_joy, report_joy},
+    user::update_user,
 };
-use crate::service::user::update_user;
 
 #[derive(Template)]
 #[template(path = "../public/index.html")]
-struct Index {
+struct Index<'a> {
+    lang: &'static str,
+    csrf: &'a str,
+    nonce: &'a str,
     app: String,
 }
 
-async fn index(State(state): State<AppState>, session: Session) -> Result<Html<String>, (StatusCode, String)> {
-    let Html(app) = component::app::show(State(state), session).await?;
-    let html = Index { app }.render().map_err(service::internal_error)?;
-    Ok(Html(html))
+async fn index(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    shell: Shell,
+) -> Result<Html<String>, (StatusCode, String)> {
+    App::build(&state, &user, shell.locale, NoProps::default())
+        .await?
+        .page(&shell)
+        .map_err(service::internal_error)
 }
 
+/// Top-level path segments routed outside the component registry.
+const RESERVED_SEGMENTS: [&str; 9] = ["", "_components", "api", "assets", "events", "favicon.ico", "joy", "locale", "user"];
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     tracing_subscriber::registry()
@@ -69,51 +108,171 @@ async fn main() -> Result<(), Box<dyn Error>> {
             panic!("Fatal Session Migration Error: {:?}", e);
         }
     }
-    let session_layer = SessionManagerLayer::new(session_store).with_secure(false);
+    let tls = TlsConfig::from_env()?;
+    let session_policy = SessionPolicy::from_env();
+
+    // Lax rather than the default Strict so that following a link to the app
+    // keeps the session; cross-site posts are stopped by the CSRF middleware.
+    let session_layer = SessionManagerLayer::new(session_store.clone())
+        .with_secure(tls.is_some())
+        .with_same_site(SameSite::Lax)
+        .with_expiry(session_policy.expiry());
 
     // Initialize services
     let _event_bus = Arc::new(EventBus::new(100));
     let sse = Arc::new(SseService::new(100));
-    let users = Arc::new(UserService::new(pool.clone()));
-    let joys = Arc::new(JoyService::new(pool.clone()));
+    let users = Arc::new(UserService::new(
+        pool.clone(),
+        LocationThreshold::from_env(),
+        HistoryRetention::from_env(),
+    ));
+    let blocklist = Blocklist::load(std::env::var("MODERATION_BLOCKLIST").ok())?;
+    let moderation = Arc::new(ModerationService::with_default_checks(blocklist));
+    let places = Arc::new(Gazetteer::load(std::env::var("GAZETTEER").ok())?);
+    let preferences = Arc::new(PreferencesService::new(pool.clone()));
+    let privacy = Arc::new(PrivacyService::new(pool.clone()));
+    let drafts = Arc::new(DraftService::new(pool.clone()));
+    let tokens = Arc::new(TokenService::new(pool.clone()));
+    let joys = Arc::new(JoyService::new(pool.clone(), moderation.clone(), places.clone()));
+    let report_threshold = std::env::var("REPORT_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REPORT_THRESHOLD);
+    let reports = Arc::new(ReportService::new(pool.clone(), report_threshold));
+    let admin = Arc::new(AdminService::new(pool.clone()));
+    let retention = Arc::new(RetentionService::new(pool.clone(), RetentionPolicy::from_env()?));
+    let rate_limiter = Arc::new(
+        RateLimiter::new(std::env::var("RATE_LIMIT_TRUST_PROXY").is_ok_and(|v| v == "true"))
+            .route(
+                Method::POST,
+                "/joy-form",
+                Limit::from_env("RATE_LIMIT_JOY_FORM", Limit::new(5, Duration::from_secs(60))),
+            )
+            .route(
+                Method::POST,
+                "/api/v1/joys",
+                Limit::from_env("RATE_LIMIT_JOY_FORM", Limit::new(5, Duration::from_secs(60))),
+            )
+            .route(
+                Method::PUT,
+                "/api/v1/location",
+                Limit::from_env("RATE_LIMIT_USER", Limit::new(30, Duration::from_secs(60))),
+            )
+            .route(
+                Method::POST,
+                "/user",
+                Limit::from_env("RATE_LIMIT_USER", Limit::new(30, Duration::from_secs(60))),
+            )
+            .route(
+                Method::GET,
+                "/user/export",
+                Limit::from_env("RATE_LIMIT_EXPORT", Limit::new(5, Duration::from_secs(60 * 60))),
+            ),
+    );
+    let location_debounce = Arc::new(Debouncer::new(Duration::from_secs(2), Duration::from_secs(10)));
+
+    let purge_limiter = rate_limiter.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            purge_limiter.purge();
+        }
+    });
+
+    spawn_cleanup(session_store, users.clone(), session_policy);
+    spawn_retention(retention.clone());
 
     // App state
     let app_state = AppState {
+        admin: admin.clone(),
         users: users.clone(),
+        drafts: drafts.clone(),
         joys: joys.clone(),
+        places: places.clone(),
+        preferences: preferences.clone(),
+        privacy: privacy.clone(),
+        reports: reports.clone(),
+        retention: retention.clone(),
         sse: sse.clone(),
+        tokens: tokens.clone(),
+        rate_limiter: rate_limiter.clone(),
+        location_debounce: location_debounce.clone(),
+        security: Arc::new(SecurityHeaders::from_env(tls.is_some())),
     };
 
     // Build routers (all share the same AppState via with_state)
-    let base: Router<AppState> = Router::new()
+    let components = component::registry::router(&RESERVED_SEGMENTS)?;
+    for route in component::registry::describe().lines() {
+        tracing::debug!("component route {}", route);
+    }
+
+    let mut base: Router<AppState> = Router::new()
         .route("/", get(index))
-        .merge(component::app::router())
-        .merge(component::joy_form::router())
-        .merge(component::joy_cards::router())
+        .merge(components)
+        .merge(api::router())
         .route("/favicon.ico", get_service(ServeFile::new("public/assets/favicon.ico")));
+    if cfg!(debug_assertions) {
+        base = base.route("/_components", get(|| async { component::registry::describe() }));
+    }
 
     let events_router: Router<AppState> = Router::new()
         .route("/events", get(sse_events));
 
     let user_router: Router<AppState> = Router::new()
-        .route("/user", post(update_user));
+        .route("/user", post(update_user))
+        .route("/user/export", get(export_data))
+        .route("/user/timezone", post(set_time_zone))
+        .route("/user/erase", post(erase_account))
+        .route("/locale", post(set_locale));
+
+    let joy_router: Router<AppState> = Router::new()
+        .route("/joy/:id", patch(update_joy))
+        .route("/joy/:id", delete(delete_joy))
+        .route("/joy/:id/reactions/:reaction", post(react_to_joy))
+        .route("/joy/:id/report", post(report_joy))
+        .route("/joy/:id/hide", post(hide_joy))
+        .route("/joy/:id/hide-author", post(hide_author));
 
     let routes = base
         .merge(events_router)
         .merge(user_router)
+        .merge(joy_router)
+        .layer(middleware::from_fn(service::csrf::middleware))
+        .layer(middleware::from_fn_with_state(app_state.clone(), service::rate_limit::middleware))
         .layer(session_layer)
-        .with_state(app_state)
+        .with_state(app_state.clone())
         .fallback_service(
             ServeDir::new("public").append_index_html_on_directories(true),
-        );
+        )
+        // Outermost, so static files get the headers too.
+        .layer(middleware::from_fn_with_state(app_state, service::security::middleware));
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:12345")
-        .await
-        .unwrap();
+    let addr: SocketAddr = std::env::var("BIND_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:12345".to_string())
+        .parse()?;
+    let app = routes.into_make_service_with_connect_info::<SocketAddr>();
 
-    tracing::debug!("listening on {}", listener.local_addr().unwrap());
+    let Some(tls) = tls else {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::debug!("listening on {}", listener.local_addr()?);
+        axum::serve(listener, app).await?;
+        return Ok(());
+    };
+
+    let rustls = tls.load().await?;
+    // Kept alive for as long as the server runs.
+    let _watcher = tls.watch(rustls.clone())?;
+    if let Some(from) = tls.redirect_from {
+        tokio::spawn(async move {
+            if let Err(e) = service::tls::redirect_to_https(from, addr.port()).await {
+                tracing::error!(error = %e, "HTTP to HTTPS redirect stopped");
+            }
+        });
+    }
 
-    axum::serve(listener, routes).await.unwrap();
+    tracing::debug!("listening on {} with TLS", addr);
+    axum_server::bind_rustls(addr, rustls).serve(app).await?;
 
     Ok(())
 }
@@ -0,0 +1,293 @@
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::service::auth::CurrentUser;
+use crate::service::moderation::Status;
+use crate::service::state::AppState;
+use crate::service::user::User;
+
+/// The current user, if they have the admin role. Rejects everyone else
+/// with 403.
+pub struct AdminUser(pub User);
+
+#[async_trait::async_trait]
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let CurrentUser(user) = CurrentUser::from_request_parts(parts, state).await?;
+
+        if !user.is_admin() {
+            return Err((StatusCode::FORBIDDEN, "admin role required".to_string()));
+        }
+
+        Ok(AdminUser(user))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct QueuedJoy {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub frustration: String,
+    pub context: String,
+    pub joy: String,
+    pub status: Status,
+    pub moderation_reason: Option<String>,
+    pub reports: i64,
+    pub created: OffsetDateTime,
+}
+
+impl QueuedJoy {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get::<Uuid, _>("id"),
+            user_id: row.get::<Option<Uuid>, _>("user_id"),
+            frustration: row.get::<String, _>("frustration"),
+            context: row.get::<String, _>("context"),
+            joy: row.get::<String, _>("joy"),
+            status: Status::parse(&row.get::<String, _>("status")).unwrap_or(Status::Held),
+            moderation_reason: row.get::<Option<String>, _>("moderation_reason"),
+            reports: row.get::<i64, _>("reports"),
+            created: row.get::<OffsetDateTime, _>("created"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UserDetail {
+    pub id: Uuid,
+    pub role: String,
+    pub banned: Option<OffsetDateTime>,
+    pub ban_reason: Option<String>,
+    pub has_point: bool,
+    pub joys: i64,
+    pub reports_against: i64,
+    pub recent: Vec<QueuedJoy>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditEntry {
+    pub moderator_id: Option<Uuid>,
+    pub action: String,
+    pub joy_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub note: Option<String>,
+    pub created: OffsetDateTime,
+}
+
+#[derive(Clone)]
+pub struct AdminService {
+    db: PgPool,
+}
+
+impl AdminService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Held joys, plus approved joys reported since they were last reviewed.
+    pub async fn queue(&self) -> Result<Vec<QueuedJoy>, String> {
+        let rows = sqlx::query(r#"
+            SELECT
+                j.id,
+                j.user_id,
+                j.frustration,
+                j.context,
+                j.joy,
+                j.status,
+                j.moderation_reason,
+                j.created,
+                (SELECT COUNT(*) FROM reports r WHERE r.joy_id = j.id) AS reports
+            FROM joys j
+            WHERE j.deleted IS NULL
+                AND (
+                    j.status = 'held'
+                    OR (j.status = 'approved' AND EXISTS (
+                        SELECT 1 FROM reports r
+                        WHERE r.joy_id = j.id
+                            AND r.created > COALESCE(j.reviewed, '-infinity'::timestamptz)
+                    ))
+                )
+            ORDER BY j.created ASC
+            LIMIT 100
+        "#)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(rows.iter().map(QueuedJoy::from_row).collect())
+    }
+
+    /// Approves or rejects a joy on behalf of `moderator_id`, recording the
+    /// decision in the audit log. Returns `false` if the joy does not exist.
+    pub async fn review(&self, joy_id: &Uuid, moderator_id: &Uuid, status: Status) -> Result<bool, String> {
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        let row = sqlx::query(r#"
+            UPDATE joys SET status = $2, reviewed = NOW()
+            WHERE id = $1 AND deleted IS NULL
+            RETURNING user_id
+        "#)
+            .bind(joy_id)
+            .bind(status.as_str())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+
+        let action = match status {
+            Status::Approved => "approve",
+            Status::Held => "hold",
+            Status::Rejected => "reject",
+        };
+        Self::record(&mut tx, moderator_id, action, Some(joy_id), row.get::<Option<Uuid>, _>("user_id").as_ref(), None).await?;
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    pub async fn find_user(&self, id: &Uuid) -> Result<Option<UserDetail>, String> {
+        let row = sqlx::query(r#"
+            SELECT
+                u.id,
+                u.role,
+                u.banned,
+                u.ban_reason,
+                u.point IS NOT NULL AS has_point,
+                (SELECT COUNT(*) FROM joys j WHERE j.user_id = u.id AND j.deleted IS NULL) AS joys,
+                (
+                    SELECT COUNT(*) FROM reports r
+                    JOIN joys j ON j.id = r.joy_id
+                    WHERE j.user_id = u.id
+                ) AS reports_against
+            FROM users u
+            WHERE u.id = $1
+        "#)
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let recent = sqlx::query(r#"
+            SELECT
+                j.id,
+                j.user_id,
+                j.frustration,
+                j.context,
+                j.joy,
+                j.status,
+                j.moderation_reason,
+                j.created,
+                (SELECT COUNT(*) FROM reports r WHERE r.joy_id = j.id) AS reports
+            FROM joys j
+            WHERE j.user_id = $1 AND j.deleted IS NULL
+            ORDER BY j.created DESC
+            LIMIT 20
+        "#)
+            .bind(id)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Some(UserDetail {
+            id: row.get::<Uuid, _>("id"),
+            role: row.get::<String, _>("role"),
+            banned: row.get::<Option<OffsetDateTime>, _>("banned"),
+            ban_reason: row.get::<Option<String>, _>("ban_reason"),
+            has_point: row.get::<bool, _>("has_point"),
+            joys: row.get::<i64, _>("joys"),
+            reports_against: row.get::<i64, _>("reports_against"),
+            recent: recent.iter().map(QueuedJoy::from_row).collect(),
+        }))
+    }
+
+    /// Bans or unbans a user, recording the decision in the audit log.
+    /// Returns `false` if the user does not exist.
+    pub async fn set_banned(&self, user_id: &Uuid, moderator_id: &Uuid, banned: bool, reason: Option<String>) -> Result<bool, String> {
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        let result = sqlx::query(r#"
+            UPDATE users SET
+                banned = CASE WHEN $2 THEN COALESCE(banned, NOW()) END,
+                ban_reason = CASE WHEN $2 THEN $3 END
+            WHERE id = $1
+        "#)
+            .bind(user_id)
+            .bind(banned)
+            .bind(&reason)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        let action = if banned { "ban" } else { "unban" };
+        Self::record(&mut tx, moderator_id, action, None, Some(user_id), reason.as_deref()).await?;
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    pub async fn audit_log(&self, limit: i64) -> Result<Vec<AuditEntry>, String> {
+        let rows = sqlx::query(r#"
+            SELECT moderator_id, action, joy_id, user_id, note, created
+            FROM moderation_actions
+            ORDER BY created DESC
+            LIMIT $1
+        "#)
+            .bind(limit)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AuditEntry {
+                moderator_id: row.get::<Option<Uuid>, _>("moderator_id"),
+                action: row.get::<String, _>("action"),
+                joy_id: row.get::<Option<Uuid>, _>("joy_id"),
+                user_id: row.get::<Option<Uuid>, _>("user_id"),
+                note: row.get::<Option<String>, _>("note"),
+                created: row.get::<OffsetDateTime, _>("created"),
+            })
+            .collect())
+    }
+
+    async fn record(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        moderator_id: &Uuid,
+        action: &str,
+        joy_id: Option<&Uuid>,
+        user_id: Option<&Uuid>,
+        note: Option<&str>,
+    ) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO moderation_actions (moderator_id, action, joy_id, user_id, note)
+            VALUES ($1, $2, $3, $4, $5)
+        "#)
+            .bind(moderator_id)
+            .bind(action)
+            .bind(joy_id)
+            .bind(user_id)
+            .bind(note)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
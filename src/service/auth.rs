@@ -0,0 +1,112 @@
+use axum::extract::{FromRequestParts, OriginalUri};
+use axum::http::request::Parts;
+use axum::http::{header, Method, StatusCode};
+use tower_sessions::Session;
+
+use crate::service::state::AppState;
+use crate::service::token::Scope;
+use crate::service::user::User;
+
+/// Personal access tokens are only honoured under this prefix; browser
+/// routes always go through the session.
+const API_PREFIX: &str = "/api/";
+
+/// The user making the request. On API routes a `Bearer` token stands in
+/// for the session cookie, and must carry `read` for safe methods and
+/// `write` for everything else. Without a token this is the session's
+/// user, created on first visit.
+///
+/// Resolved once per request: the first extractor to look the user up
+/// leaves them in the request extensions for the rest.
+#[derive(Clone)]
+pub struct CurrentUser(pub User);
+
+/// Like [`CurrentUser`], but never creates a user. Requests without a
+/// token or a session that already has one are rejected with 401, so
+/// cookie-less clients cannot act on existing joys or mint users.
+pub struct RequireAccount(pub User);
+
+pub(crate) fn bearer(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+fn required_scope(method: &Method) -> Scope {
+    if method.is_safe() {
+        Scope::Read
+    } else {
+        Scope::Write
+    }
+}
+
+/// Finds the request's user, creating an anonymous one for a new session
+/// if `create` is set.
+async fn resolve(parts: &mut Parts, state: &AppState, create: bool) -> Result<Option<User>, (StatusCode, String)> {
+    if let Some(CurrentUser(user)) = parts.extensions.get::<CurrentUser>() {
+        return Ok(Some(user.clone()));
+    }
+
+    let user = if let Some(secret) = bearer(parts) {
+        // Nested routers see a stripped path; the original is what matters.
+        let path = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or_else(|| parts.uri.path(), |OriginalUri(uri)| uri.path());
+        if !path.starts_with(API_PREFIX) {
+            return Err((StatusCode::UNAUTHORIZED, "API tokens are only accepted under /api".to_string()));
+        }
+
+        let token = state.tokens.authenticate(secret).await
+            .map_err(crate::service::internal_error)?
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "invalid or expired token".to_string()))?;
+
+        let scope = required_scope(&parts.method);
+        if !token.allows(scope) {
+            return Err((StatusCode::FORBIDDEN, format!("token lacks the {} scope", scope.as_str())));
+        }
+
+        Some(state.users.get_by_id(&token.user_id).await.map_err(crate::service::internal_error)?)
+    } else {
+        let session = Session::from_request_parts(parts, state)
+            .await
+            .map_err(|(status, msg)| (status, msg.to_string()))?;
+
+        if create {
+            Some(state.users.get_or_create_session_user(session).await.map_err(crate::service::internal_error)?)
+        } else {
+            state.users.get_session_user(&session).await.map_err(crate::service::internal_error)?
+        }
+    };
+
+    if let Some(user) = &user {
+        parts.extensions.insert(CurrentUser(user.clone()));
+    }
+    Ok(user)
+}
+
+#[async_trait::async_trait]
+impl FromRequestParts<AppState> for CurrentUser {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = resolve(parts, state, true).await?
+            .ok_or_else(|| crate::service::internal_error("no user for session"))?;
+        Ok(CurrentUser(user))
+    }
+}
+
+#[async_trait::async_trait]
+impl FromRequestParts<AppState> for RequireAccount {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = resolve(parts, state, false).await?
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "no session; load the app first or use an API token".to_string()))?;
+        Ok(RequireAccount(user))
+    }
+}
@@ -0,0 +1,105 @@
+use axum::body::{to_bytes, Body};
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use tower_sessions::Session;
+
+use crate::component::flash::Flash;
+use crate::service::user::session_csrf_token;
+
+/// Header Datastar and other scripts send the token in.
+pub const HEADER: &str = "x-csrf-token";
+
+/// Form bodies are buffered to read the token; none of ours come close.
+const MAX_FORM_BYTES: usize = 64 * 1024;
+
+/// Compares tokens in time independent of where they first differ.
+fn same_token(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[derive(Default, Deserialize)]
+struct CsrfField {
+    #[serde(default)]
+    csrf: String,
+}
+
+/// Whether the browser says the request comes from another site. Older
+/// browsers without `Sec-Fetch-Site` are judged by `Origin` against `Host`.
+fn is_cross_site(headers: &HeaderMap) -> bool {
+    let get = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    if let Some(site) = get(header::HeaderName::from_static("sec-fetch-site")) {
+        return !matches!(site, "same-origin" | "none");
+    }
+    match (get(header::ORIGIN), get(header::HOST)) {
+        (Some(origin), Some(host)) => origin.split_once("://").map(|(_, authority)| authority) != Some(host),
+        _ => false,
+    }
+}
+
+fn is_form(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"))
+}
+
+fn is_api_token_call(req: &Request) -> bool {
+    req.uri().path().starts_with("/api/")
+        && req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("Bearer "))
+}
+
+fn rejected(message: &str) -> Response {
+    tracing::info!(message, "CSRF check failed");
+    Flash::error(message).into_response_with(StatusCode::FORBIDDEN)
+}
+
+/// Guards every unsafe request made with the session cookie: cross-site
+/// requests are refused outright, and the rest must carry the session's
+/// token, as the `csrf` field of a form post or in the `X-CSRF-Token`
+/// header. Tokens are random per session and replaced whenever the session
+/// ID is, so a leaked one dies with its session.
+/// Bearer-token API calls carry no cookie and are left alone. Must run
+/// inside the session layer.
+pub async fn middleware(session: Session, req: Request, next: Next) -> Response {
+    if req.method().is_safe() || is_api_token_call(&req) {
+        return next.run(req).await;
+    }
+    if is_cross_site(req.headers()) {
+        return rejected("Cross-site requests are not allowed.");
+    }
+
+    let expected = match session_csrf_token(&session).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return rejected("Your session has expired. Please reload the page."),
+        Err(e) => return crate::service::internal_error(e).into_response(),
+    };
+
+    let req = if is_form(req.headers()) {
+        let (parts, body) = req.into_parts();
+        let bytes = match to_bytes(body, MAX_FORM_BYTES).await {
+            Ok(bytes) => bytes,
+            Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+        };
+        let CsrfField { csrf } = serde_urlencoded::from_bytes(&bytes).unwrap_or_default();
+        if !same_token(&expected, &csrf) {
+            return rejected("This form has expired. Please reload the page and try again.");
+        }
+        Request::from_parts(parts, Body::from(bytes))
+    } else {
+        let token = req.headers().get(HEADER).and_then(|value| value.to_str().ok()).unwrap_or_default();
+        if !same_token(&expected, token) {
+            return rejected("This page has expired. Please reload it and try again.");
+        }
+        req
+    };
+
+    next.run(req).await
+}
@@ -0,0 +1,132 @@
+use fluent_bundle::FluentArgs;
+use time::{Month, OffsetDateTime, Weekday};
+
+use crate::service::i18n::message;
+use crate::service::locale::Locale;
+
+fn weekday(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Monday => "weekday-mon",
+        Weekday::Tuesday => "weekday-tue",
+        Weekday::Wednesday => "weekday-wed",
+        Weekday::Thursday => "weekday-thu",
+        Weekday::Friday => "weekday-fri",
+        Weekday::Saturday => "weekday-sat",
+        Weekday::Sunday => "weekday-sun",
+    }
+}
+
+fn month(month: Month) -> &'static str {
+    match month {
+        Month::January => "month-jan",
+        Month::February => "month-feb",
+        Month::March => "month-mar",
+        Month::April => "month-apr",
+        Month::May => "month-may",
+        Month::June => "month-jun",
+        Month::July => "month-jul",
+        Month::August => "month-aug",
+        Month::September => "month-sep",
+        Month::October => "month-oct",
+        Month::November => "month-nov",
+        Month::December => "month-dec",
+    }
+}
+
+/// Server-side counterpart of `DateHelper.format` in `date.ts`, so the first
+/// render already reads naturally. Times are shown in the offset of `now`,
+/// which callers take from the user's stored offset, falling back to UTC.
+pub fn format(date: OffsetDateTime, now: OffsetDateTime, locale: Locale) -> String {
+    let diff = now - date;
+    let minutes = diff.whole_minutes();
+
+    // 1. Relative time (up to 30 minutes)
+    if minutes < 1 {
+        let seconds = diff.whole_seconds();
+        return if seconds < 5 {
+            locale.t("date-just-now")
+        } else {
+            locale.t_count("date-seconds-ago", seconds)
+        };
+    }
+    if minutes < 30 {
+        return locale.t_count("date-minutes-ago", minutes);
+    }
+
+    let date = date.to_offset(now.offset());
+    let time = format!("{:02}:{:02}", date.hour(), date.minute());
+
+    // 2. Yesterday's time ("16:49 Yesterday")
+    if (now.date() - date.date()).whole_days() == 1 {
+        let mut args = FluentArgs::new();
+        args.set("time", time);
+        return message(locale, "date-yesterday", Some(&args));
+    }
+
+    // 3. Current year ("16:49 Mon 23 Jan"), 4. older ("16:49 Mon 23 Jan 2025")
+    if date.year() == now.year() {
+        format!("{} {}", time, day(date, locale))
+    } else {
+        format!("{} {} {}", time, day(date, locale), date.year())
+    }
+}
+
+/// Just the calendar day, e.g. "Mon 23 Jan 2025", for dates that may lie in
+/// the future such as expiries.
+pub fn format_day(date: OffsetDateTime, locale: Locale) -> String {
+    format!("{} {}", day(date, locale), date.year())
+}
+
+fn day(date: OffsetDateTime, locale: Locale) -> String {
+    let (weekday, month) = (locale.t(weekday(date.weekday())), locale.t(month(date.month())));
+    match locale {
+        Locale::EnUs => format!("{} {} {:02}", weekday, month, date.day()),
+        _ => format!("{} {:02} {}", weekday, date.day(), month),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Duration, PrimitiveDateTime, Time, UtcOffset};
+
+    fn at(year: i32, month: Month, day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+        let date = Date::from_calendar_date(year, month, day).unwrap();
+        PrimitiveDateTime::new(date, Time::from_hms(hour, minute, 0).unwrap()).assume_utc()
+    }
+
+    #[test]
+    fn recent_times_are_relative() {
+        let now = at(2025, Month::January, 23, 10, 0);
+        assert_eq!(format(now - Duration::seconds(3), now, Locale::EnGb), "Just now");
+        assert_eq!(format(now - Duration::seconds(30), now, Locale::EnGb), "30 seconds ago");
+        assert_eq!(format(now - Duration::minutes(1), now, Locale::EnGb), "1 minute ago");
+        assert_eq!(format(now - Duration::minutes(29), now, Locale::EnGb), "29 minutes ago");
+    }
+
+    #[test]
+    fn older_times_show_the_day() {
+        let now = at(2025, Month::January, 23, 10, 0);
+        assert_eq!(format(at(2025, Month::January, 22, 16, 49), now, Locale::EnGb), "16:49 Yesterday");
+        assert_eq!(format(at(2025, Month::January, 22, 16, 49), now, Locale::Fr), "hier 16:49");
+        assert_eq!(format(at(2025, Month::January, 20, 16, 49), now, Locale::EnGb), "16:49 Mon 20 Jan");
+        assert_eq!(format(at(2025, Month::January, 20, 16, 49), now, Locale::EnUs), "16:49 Mon Jan 20");
+        assert_eq!(format(at(2024, Month::December, 30, 8, 5), now, Locale::EnGb), "08:05 Mon 30 Dec 2024");
+    }
+
+    #[test]
+    fn times_follow_the_offset_of_now() {
+        let joy = at(2025, Month::January, 22, 21, 0);
+        let now = at(2025, Month::January, 22, 22, 30);
+        assert_eq!(format(joy, now, Locale::EnGb), "21:00 Wed 22 Jan");
+
+        // Half past midnight in UTC+2: the joy was written late yesterday.
+        let paris = now.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+        assert_eq!(format(joy, paris, Locale::EnGb), "23:00 Yesterday");
+    }
+
+    #[test]
+    fn format_day_includes_the_year() {
+        assert_eq!(format_day(at(2026, Month::March, 2, 12, 0), Locale::EnGb), "Mon 02 Mar 2026");
+    }
+}
@@ -16,14 +16,14 @@ export class DateHelper {
         }
     }
 
-    public static computedFormat(dateGetter: () => Date) {
+    public static computedFormat(dateGetter: () => Date, localeGetter: () => string = () => 'en-US') {
         this.ensureTicker()
         return computed(() => {
-            return DateHelper.format(dateGetter(), this._now())
+            return DateHelper.format(dateGetter(), this._now(), localeGetter())
         })
     }
 
-    public static format(date: Date, now: Date): string {
+    public static format(date: Date, now: Date, locale: string = 'en-US'): string {
         if (!date) {
             return '';
         }
@@ -46,16 +46,27 @@ export class DateHelper {
                 month: 'short',
                 year: includeYear ? 'numeric' : undefined,
             }
-            return d.toLocaleString('en-US', options).replace(/,/g, '')
+            return d.toLocaleString(locale, options).replace(/,/g, '')
         }
 
+        // English keeps its own wording; other locales use Intl, which the
+        // server-side catalogs mirror.
+        const english = locale.startsWith('en')
+        const relative = new Intl.RelativeTimeFormat(locale, {numeric: 'auto'})
+
         // 1. Relative Time (Up to 30 minutes)
         if (diffMinutes < 1) {
             const diffSeconds = Math.floor(diffMs / 1000)
+            if (!english) {
+                return relative.format(diffSeconds < 5 ? 0 : -diffSeconds, 'second')
+            }
             const s = diffSeconds === 1 ? '' : 's'
             return diffSeconds < 5 ? 'Just now' : `${diffSeconds} second${s} ago`
         }
         if (diffMinutes < 30) {
+            if (!english) {
+                return relative.format(-diffMinutes, 'minute')
+            }
             const s = diffMinutes === 1 ? '' : 's'
             return `${diffMinutes} minute${s} ago`
         }
@@ -67,7 +78,9 @@ export class DateHelper {
 
         // 2. Yesterday's Time ("16:49 Yesterday")
         if (diffDaysExact >= 1 && diffDaysExact < 2) {
-            return `${formatTime(date)} Yesterday`
+            return english
+                ? `${formatTime(date)} Yesterday`
+                : `${relative.format(-1, 'day')} ${formatTime(date)}`
         }
 
         // 3. Current Year, Older than Yesterday ("16:49 Mon 23 Jan")
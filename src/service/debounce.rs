@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+struct Pending {
+    generation: u64,
+    since: Instant,
+}
+
+/// Per-key trailing debounce: an action runs once calls for its key have
+/// been quiet for `delay`, or at the latest `max_wait` after the first
+/// call, so a continuous stream of calls still gets through now and then.
+#[derive(Clone)]
+pub struct Debouncer {
+    delay: Duration,
+    max_wait: Duration,
+    pending: Arc<Mutex<HashMap<Uuid, Pending>>>,
+    /// Never reset, so a task still sleeping from a fired or overdue cycle
+    /// can't mistake a newer entry for its own.
+    generations: Arc<AtomicU64>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration, max_wait: Duration) -> Self {
+        Self {
+            delay,
+            max_wait,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            generations: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn trigger<F>(&self, key: Uuid, action: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let now = Instant::now();
+        let (generation, overdue) = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = pending.entry(key).or_insert(Pending { generation: 0, since: now });
+            let generation = self.generations.fetch_add(1, Ordering::Relaxed) + 1;
+            entry.generation = generation;
+            let overdue = now.duration_since(entry.since) >= self.max_wait;
+            if overdue {
+                pending.remove(&key);
+            }
+            (generation, overdue)
+        };
+
+        if overdue {
+            action();
+            return;
+        }
+
+        let pending = self.pending.clone();
+        let delay = self.delay;
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let latest = {
+                let mut pending = pending.lock().unwrap_or_else(|e| e.into_inner());
+                match pending.get(&key) {
+                    Some(entry) if entry.generation == generation => {
+                        pending.remove(&key);
+                        true
+                    }
+                    _ => false,
+                }
+            };
+            if latest {
+                action();
+            }
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use fluent_bundle::{FluentArgs, FluentValue};
+
+use crate::service::i18n::message;
+use crate::service::locale::{Locale, Units};
+
+const ONE_MILE: f64 = 1609.344;
+const ONE_YARD: f64 = 0.9144;
+
+/// Formats `value` with `digits` decimals into the `$distance` argument of
+/// message `id`, with the unlocalised number as `$count` for plurals.
+fn amount(locale: Locale, id: &str, value: f64, digits: usize) -> String {
+    let count = format!("{:.*}", digits, value);
+    let mut args = FluentArgs::new();
+    args.set("distance", locale.decimal(value, digits));
+    args.set("count", FluentValue::try_number(&count).into_owned());
+    message(locale, id, Some(&args))
+}
+
+/// Formats a distance from the viewer, e.g. "1.2 miles" or "Right here".
+pub fn format(metres: Option<f64>, units: Units, locale: Locale) -> String {
+    let Some(value) = metres else {
+        return String::new();
+    };
+    if value < 100.0 {
+        return locale.t("distance-right-here");
+    }
+
+    match units {
+        Units::Imperial => {
+            let miles = value / ONE_MILE;
+            if miles < 0.1 {
+                let yards = (value / ONE_YARD / 10.0).round() * 10.0;
+                return amount(locale, "distance-yards", yards, 0);
+            }
+            if miles >= 1000.0 {
+                return amount(locale, "distance-thousand-miles", miles / 1000.0, 1);
+            }
+            if (miles * 10.0).round() == 10.0 {
+                return locale.t("distance-one-mile");
+            }
+            amount(locale, "distance-miles", miles, 1)
+        }
+        Units::Metric => {
+            let km = value / 1000.0;
+            if km >= 1000.0 {
+                return amount(locale, "distance-thousand-kilometres", km / 1000.0, 1);
+            }
+            if km >= 1.0 {
+                return amount(locale, "distance-kilometres", km, 1);
+            }
+            amount(locale, "distance-metres", value, 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_and_nearby_distances() {
+        assert_eq!(format(None, Units::Metric, Locale::EnGb), "");
+        assert_eq!(format(Some(50.0), Units::Metric, Locale::EnGb), "Right here");
+        assert_eq!(format(Some(50.0), Units::Imperial, Locale::Fr), "Ici même");
+    }
+
+    #[test]
+    fn metric_distances() {
+        assert_eq!(format(Some(500.0), Units::Metric, Locale::EnGb), "500m");
+        assert_eq!(format(Some(1500.0), Units::Metric, Locale::EnGb), "1.5 km");
+        assert_eq!(format(Some(1500.0), Units::Metric, Locale::Fr), "1,5 km");
+        assert_eq!(format(Some(2_500_000.0), Units::Metric, Locale::EnGb), "2.5k km");
+    }
+
+    #[test]
+    fn imperial_distances() {
+        assert_eq!(format(Some(120.0), Units::Imperial, Locale::EnUs), "130 yd");
+        assert_eq!(format(Some(500.0), Units::Imperial, Locale::EnUs), "0.3 miles");
+        assert_eq!(format(Some(1200.0), Units::Imperial, Locale::EnUs), "0.7 miles");
+        assert_eq!(format(Some(1609.0), Units::Imperial, Locale::EnUs), "1 mile");
+        assert_eq!(format(Some(1200.0), Units::Imperial, Locale::Fr), "0,7 mille");
+        assert_eq!(format(Some(2.0 * ONE_MILE), Units::Imperial, Locale::EnUs), "2.0 miles");
+        assert_eq!(format(Some(1500.0 * ONE_MILE), Units::Imperial, Locale::EnUs), "1.5k miles");
+    }
+}
@@ -0,0 +1,170 @@
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Where the joy form is: the welcome screen, then one question per step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Step {
+    #[default]
+    Start,
+    Frustration,
+    Context,
+    Joy,
+}
+
+impl Step {
+    pub const ALL: [Step; 4] = [Step::Start, Step::Frustration, Step::Context, Step::Joy];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Step::Start => "start",
+            Step::Frustration => "frustration",
+            Step::Context => "context",
+            Step::Joy => "joy",
+        }
+    }
+
+    fn index(&self) -> i16 {
+        Self::ALL.iter().position(|s| s == self).unwrap_or_default() as i16
+    }
+
+    fn from_index(index: i16) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    /// The step after this one; the last step has none.
+    pub fn next(&self) -> Self {
+        Self::ALL.get(self.index() as usize + 1).copied().unwrap_or(*self)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Answers {
+    pub frustration: String,
+    pub context: String,
+    pub joy: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Draft {
+    pub step: Step,
+    pub answers: Answers,
+}
+
+impl Draft {
+    /// The answer being written on the current step.
+    pub fn current_answer(&self) -> Option<&str> {
+        match self.step {
+            Step::Start => None,
+            Step::Frustration => Some(&self.answers.frustration),
+            Step::Context => Some(&self.answers.context),
+            Step::Joy => Some(&self.answers.joy),
+        }
+    }
+
+    /// Whether the form has reached its last step with every question
+    /// answered, so the draft can be posted.
+    pub fn is_ready(&self) -> bool {
+        let answers = &self.answers;
+        self.step == Step::Joy
+            && [&answers.frustration, &answers.context, &answers.joy]
+                .iter()
+                .all(|answer| !answer.trim().is_empty())
+    }
+}
+
+pub struct DraftService {
+    db: PgPool,
+}
+
+impl DraftService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// The user's draft, or a fresh one at the start screen.
+    pub async fn get(&self, user_id: &Uuid) -> Result<Draft, String> {
+        let row = sqlx::query(r#"
+            SELECT step, frustration, context, joy
+            FROM joy_drafts
+            WHERE user_id = $1
+        "#)
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(row) = row else {
+            return Ok(Draft::default());
+        };
+
+        Ok(Draft {
+            step: Step::from_index(row.get::<i16, _>("step")),
+            answers: Answers {
+                frustration: row.get::<String, _>("frustration"),
+                context: row.get::<String, _>("context"),
+                joy: row.get::<String, _>("joy"),
+            },
+        })
+    }
+
+    pub async fn save(&self, user_id: &Uuid, draft: &Draft) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO joy_drafts (user_id, step, frustration, context, joy)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE SET
+                step = EXCLUDED.step,
+                frustration = EXCLUDED.frustration,
+                context = EXCLUDED.context,
+                joy = EXCLUDED.joy,
+                updated = NOW()
+        "#)
+            .bind(user_id)
+            .bind(draft.step.index())
+            .bind(&draft.answers.frustration)
+            .bind(&draft.answers.context)
+            .bind(&draft.answers.joy)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Brings an existing draft's answers up to date without creating one,
+    /// so a late submit cannot resurrect a draft that was already consumed.
+    /// Returns whether there was a draft to update.
+    pub async fn update_answers(&self, user_id: &Uuid, answers: &Answers) -> Result<bool, String> {
+        let result = sqlx::query(r#"
+            UPDATE joy_drafts
+            SET frustration = $2, context = $3, joy = $4, updated = NOW()
+            WHERE user_id = $1
+        "#)
+            .bind(user_id)
+            .bind(&answers.frustration)
+            .bind(&answers.context)
+            .bind(&answers.joy)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answered(frustration: &str, context: &str, joy: &str) -> Answers {
+        Answers { frustration: frustration.to_string(), context: context.to_string(), joy: joy.to_string() }
+    }
+
+    #[test]
+    fn only_complete_drafts_on_the_last_step_are_ready() {
+        let answers = answered("late bus", "raining", "a stranger shared their umbrella");
+        assert!(Draft { step: Step::Joy, answers: answers.clone() }.is_ready());
+        assert!(!Draft { step: Step::Context, answers }.is_ready());
+        assert!(!Draft { step: Step::Joy, answers: answered("late bus", "  ", "umbrella") }.is_ready());
+        assert!(!Draft::default().is_ready());
+    }
+}
@@ -0,0 +1,199 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::service::locale::Locale;
+
+const DEFAULT_GAZETTEER: &str = include_str!("../../gazetteer/cities.tsv");
+
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Joys further than this from every known place get no label.
+const MAX_LABEL_DISTANCE: f64 = 50_000.0;
+
+/// Within this distance a joy is labelled "in" a place rather than "near" it.
+const NEAR_DISTANCE: f64 = 10_000.0;
+
+#[derive(Clone, Debug)]
+pub struct Place {
+    pub id: usize,
+    pub name: String,
+    ascii: String,
+    pub country: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub population: u64,
+}
+
+impl Place {
+    pub fn label(&self) -> String {
+        format!("{}, {}", self.name, self.country)
+    }
+}
+
+/// A coarse place name for a point, worded for the viewer's locale when
+/// rendered.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct PlaceLabel {
+    /// The place's name and country, e.g. "Leeds, GB".
+    pub name: String,
+    /// Whether the point is only near the place rather than in it.
+    pub near: bool,
+}
+
+impl PlaceLabel {
+    pub fn localised(&self, locale: &Locale) -> String {
+        if self.near {
+            locale.t_arg("place-near", "place", self.name.as_str())
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// Great-circle distance in metres.
+pub fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+fn cell(latitude: f64, longitude: f64) -> (i32, i32) {
+    (latitude.floor() as i32, longitude.floor() as i32)
+}
+
+fn normalise(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+fn parse_row(id: usize, line: &str) -> Option<Place> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [name, ascii, country, latitude, longitude, population] = fields.as_slice() else {
+        return None;
+    };
+    Some(Place {
+        id,
+        name: name.to_string(),
+        ascii: normalise(ascii),
+        country: country.to_string(),
+        latitude: latitude.parse().ok()?,
+        longitude: longitude.parse().ok()?,
+        population: population.parse().ok()?,
+    })
+}
+
+/// An offline city list, indexed by name for search and by one-degree grid
+/// cell for reverse geocoding.
+pub struct Gazetteer {
+    places: Vec<Place>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Gazetteer {
+    /// Parses tab-separated `name, ascii name, country, latitude, longitude,
+    /// population` lines, skipping comments and malformed rows.
+    pub fn parse(source: &str) -> Self {
+        let mut places = Vec::new();
+        for (n, line) in source.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_row(places.len(), line) {
+                Some(place) => places.push(place),
+                None => tracing::warn!(line = n + 1, "skipping malformed gazetteer row"),
+            }
+        }
+
+        let mut cells: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for place in &places {
+            cells.entry(cell(place.latitude, place.longitude)).or_default().push(place.id);
+        }
+
+        Self { places, cells }
+    }
+
+    /// Loads the gazetteer at `path`, falling back to the bundled city list.
+    pub fn load(path: Option<String>) -> Result<Self, String> {
+        match path {
+            Some(path) => std::fs::read_to_string(&path)
+                .map(|s| Self::parse(&s))
+                .map_err(|e| format!("failed to read gazetteer {}: {}", path, e)),
+            None => Ok(Self::parse(DEFAULT_GAZETTEER)),
+        }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Place> {
+        self.places.get(id)
+    }
+
+    /// Places whose name, or any word of it, starts with `query`, largest first.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&Place> {
+        let query = normalise(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<&Place> = self.places
+            .iter()
+            .filter(|p| {
+                p.ascii.starts_with(&query)
+                    || p.name.to_lowercase().starts_with(&query)
+                    || p.ascii.split([' ', '-']).any(|w| w.starts_with(&query))
+            })
+            .collect();
+        matches.sort_by_key(|p| std::cmp::Reverse(p.population));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// The closest place within `max_distance` metres, and how far away it is.
+    pub fn nearest(&self, latitude: f64, longitude: f64, max_distance: f64) -> Option<(&Place, f64)> {
+        let (lat_cell, lon_cell) = cell(latitude, longitude);
+        let lat_span = (max_distance / 111_000.0).ceil() as i32;
+        let lon_span = match latitude.to_radians().cos() {
+            c if c > 0.01 => ((max_distance / (111_000.0 * c)).ceil() as i32).min(180),
+            _ => 180,
+        };
+
+        let mut best: Option<(&Place, f64)> = None;
+        for dlat in -lat_span..=lat_span {
+            for dlon in -lon_span..=lon_span {
+                let lon = (lon_cell + dlon + 180).rem_euclid(360) - 180;
+                let Some(ids) = self.cells.get(&(lat_cell + dlat, lon)) else {
+                    continue;
+                };
+                for place in ids.iter().map(|&id| &self.places[id]) {
+                    let d = distance(latitude, longitude, place.latitude, place.longitude);
+                    if d <= max_distance && best.is_none_or(|(_, b)| d < b) {
+                        best = Some((place, d));
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// A coarse place name for a point, e.g. Leeds, GB, or near it.
+    pub fn label(&self, latitude: f64, longitude: f64) -> Option<PlaceLabel> {
+        self.nearest(latitude, longitude, MAX_LABEL_DISTANCE)
+            .map(|(place, d)| PlaceLabel { name: place.label(), near: d > NEAR_DISTANCE })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_worded_per_locale() {
+        let gazetteer = Gazetteer::parse("Leeds\tLeeds\tGB\t53.7965\t-1.5478\t455123\n");
+        let label = gazetteer.label(53.7965, -1.5478).unwrap();
+        assert_eq!(label.localised(&Locale::EnGb), "Leeds, GB");
+
+        let label = gazetteer.label(53.9, -1.5478).unwrap();
+        assert_eq!(label.localised(&Locale::EnGb), "near Leeds, GB");
+        assert_eq!(label.localised(&Locale::Fr), "près de Leeds, GB");
+    }
+}
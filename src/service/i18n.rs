@@ -0,0 +1,191 @@
+use axum::extract::{FromRequestParts, State};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::Redirect;
+use axum::Form;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_langneg::{accepted_languages, negotiate_languages, NegotiationStrategy};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tower_sessions::Session;
+use unic_langid::LanguageIdentifier;
+
+use crate::service::auth::CurrentUser;
+use crate::service::locale::Locale;
+use crate::service::state::AppState;
+use crate::service::user::session_user_id;
+
+const SESSION_LOCALE_KEY: &str = "locale";
+
+const EN: &str = include_str!("../../locales/en/main.ftl");
+const FR: &str = include_str!("../../locales/fr/main.ftl");
+
+/// One Fluent bundle per supported locale. English is also the fallback for
+/// any message missing from another catalog.
+struct Catalogs {
+    bundles: HashMap<Locale, FluentBundle<FluentResource>>,
+}
+
+impl Catalogs {
+    fn load() -> Self {
+        let bundles = Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let source = match locale {
+                    Locale::EnUs | Locale::EnGb => EN,
+                    Locale::Fr => FR,
+                };
+                let resource = FluentResource::try_new(source.to_string())
+                    .unwrap_or_else(|(_, errors)| panic!("invalid {} catalog: {:?}", locale.tag(), errors));
+                let mut bundle = FluentBundle::new_concurrent(vec![locale.langid()]);
+                // Bidi isolation marks would end up inside HTML attributes.
+                bundle.set_use_isolating(false);
+                bundle
+                    .add_resource(resource)
+                    .unwrap_or_else(|errors| panic!("duplicate messages in {} catalog: {:?}", locale.tag(), errors));
+                (locale, bundle)
+            })
+            .collect();
+        Self { bundles }
+    }
+
+    fn format(&self, locale: Locale, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let bundle = self.bundles.get(&locale)?;
+        let pattern = bundle.get_message(id)?.value()?;
+        let mut errors = vec![];
+        let value = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            tracing::warn!(id, locale = locale.tag(), ?errors, "errors formatting message");
+        }
+        Some(value.into_owned())
+    }
+}
+
+static CATALOGS: LazyLock<Catalogs> = LazyLock::new(Catalogs::load);
+
+/// Looks up `id` in the locale's catalog, falling back to English and then
+/// to the id itself.
+pub fn message(locale: Locale, id: &str, args: Option<&FluentArgs>) -> String {
+    CATALOGS
+        .format(locale, id, args)
+        .or_else(|| CATALOGS.format(Locale::default(), id, args))
+        .unwrap_or_else(|| {
+            tracing::warn!(id, locale = locale.tag(), "missing message");
+            id.to_string()
+        })
+}
+
+/// Template helpers: `{{ locale.t("id") }}`, `{{ locale.t_arg("id", "name", value) }}`
+/// and, for plurals, `{{ locale.t_count("id", n) }}`.
+impl Locale {
+    pub fn t(&self, id: &str) -> String {
+        message(*self, id, None)
+    }
+
+    pub fn t_arg<'a>(&self, id: &str, name: &'a str, value: impl Into<FluentValue<'a>>) -> String {
+        let mut args = FluentArgs::new();
+        args.set(name, value.into());
+        message(*self, id, Some(&args))
+    }
+
+    pub fn t_count<'a>(&self, id: &str, count: impl Into<FluentValue<'a>>) -> String {
+        self.t_arg(id, "count", count)
+    }
+}
+
+/// Picks the best supported locale from an `Accept-Language` header.
+pub fn negotiate(headers: &HeaderMap) -> Locale {
+    let Some(accept) = headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok()) else {
+        return Locale::default();
+    };
+    let requested = accepted_languages::parse(accept);
+    let available: Vec<LanguageIdentifier> = Locale::ALL.iter().map(Locale::langid).collect();
+    let default = Locale::default().langid();
+    negotiate_languages(&requested, &available, Some(&default), NegotiationStrategy::Filtering)
+        .first()
+        .and_then(|langid| Locale::parse(&langid.to_string()))
+        .unwrap_or_default()
+}
+
+/// Remembers `locale` for this session, or forgets it with `None`.
+pub async fn set_session_locale(session: &Session, locale: Option<Locale>) -> Result<(), String> {
+    let result = match locale {
+        Some(locale) => session.insert(SESSION_LOCALE_KEY, locale.tag()).await,
+        None => session.remove::<String>(SESSION_LOCALE_KEY).await.map(|_| ()),
+    };
+    result.map_err(|e| format!("Session write error: {}", e))
+}
+
+/// The request's locale: the one chosen in this session, else the user's
+/// saved preference, else the best match for `Accept-Language`.
+#[async_trait::async_trait]
+impl FromRequestParts<AppState> for Locale {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let session = Session::from_request_parts(parts, state)
+            .await
+            .map_err(|(status, msg)| (status, msg.to_string()))?;
+
+        let chosen: Option<String> = session.get(SESSION_LOCALE_KEY).await
+            .map_err(crate::service::internal_error)?;
+        if let Some(locale) = chosen.as_deref().and_then(Locale::parse) {
+            return Ok(locale);
+        }
+
+        // Reuse the user if an earlier extractor already resolved them.
+        let user_id = match parts.extensions.get::<CurrentUser>() {
+            Some(CurrentUser(user)) => Some(user.id),
+            None => session_user_id(&session).await.map_err(crate::service::internal_error)?,
+        };
+        if let Some(user_id) = user_id {
+            let preferences = state.preferences.get(&user_id).await
+                .map_err(crate::service::internal_error)?;
+            if let Some(locale) = preferences.locale {
+                return Ok(locale);
+            }
+        }
+
+        Ok(negotiate(&parts.headers))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LocaleForm {
+    locale: String,
+}
+
+/// Stores the chosen locale in the session and the user's preferences, and
+/// sends them back to the page they came from.
+pub async fn set_locale(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    session: Session,
+    headers: HeaderMap,
+    Form(form): Form<LocaleForm>,
+) -> Result<Redirect, (StatusCode, String)> {
+    let locale = Locale::parse(&form.locale)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("unsupported locale: {}", form.locale)))?;
+
+    set_session_locale(&session, Some(locale)).await
+        .map_err(crate::service::internal_error)?;
+
+    state.preferences.set_locale(&user.id, locale).await
+        .map_err(crate::service::internal_error)?;
+
+    // Only follow same-origin referers.
+    let back = headers
+        .get(header::REFERER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|r| r.parse::<axum::http::Uri>().ok())
+        .filter(|uri| {
+            let host = headers.get(header::HOST).and_then(|h| h.to_str().ok());
+            uri.authority().map(|a| a.as_str()) == host
+        })
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| "/".to_string());
+
+    Ok(Redirect::to(&back))
+}
@@ -1,30 +1,214 @@
-use sqlx::{PgPool, Row};
-use time::OffsetDateTime;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use axum::response::{Html, IntoResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
 use uuid::Uuid;
-use serde::Serialize;
 
-#[derive(Clone, Debug, Serialize)]
+use std::sync::Arc;
+
+use crate::component::joy_card::{JoyCard, JoyCardProps};
+use crate::component::Component;
+use crate::service::auth::RequireAccount;
+use crate::service::gazetteer::{Gazetteer, PlaceLabel};
+use crate::service::locale::Locale;
+use crate::service::moderation::{ModerationService, Status};
+use crate::service::preferences::{Preferences, Ranking};
+use crate::service::sse::Patch;
+use crate::service::state::AppState;
+use crate::service::user::User;
+
+/// How long after creation the owner may still edit a joy.
+pub const EDIT_WINDOW: Duration = Duration::minutes(15);
+
+/// Serializes as seen by a viewer: the author is never exposed, the
+/// frustration, context and accuracy only appear for the owner, and anyone
+/// else gets coordinates rounded to about a kilometre.
+#[derive(Clone, Debug, Serialize, ToSchema)]
 pub struct Joy {
     pub id: Uuid,
-    pub user_id: Uuid,
+    /// `None` once the joy has been anonymised by the retention job.
+    #[serde(skip)]
+    pub user_id: Option<Uuid>,
+    /// Exact for the owner, rounded to two decimal places for everyone else.
     pub longitude: Option<f64>,
+    /// Exact for the owner, rounded to two decimal places for everyone else.
     pub latitude: Option<f64>,
+    /// Accuracy in metres of the location fix the joy was written at. Only
+    /// present for the owner.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub frustration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<String>,
     pub joy: String,
     #[serde(with = "time::serde::iso8601")]
+    #[schema(value_type = String, format = DateTime)]
     pub created: OffsetDateTime,
+    #[serde(with = "time::serde::iso8601::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub updated: Option<OffsetDateTime>,
+    /// Metres from the viewer's last known location.
     pub distance: Option<f64>,
+    /// Coarse place name for the joy's location, from the gazetteer; clients
+    /// word `near` themselves.
+    pub place: Option<PlaceLabel>,
+    pub reactions: Reactions,
+    /// The viewing user's own reaction, if any.
+    pub reaction: Option<Reaction>,
+    pub status: Status,
+    #[serde(skip)]
+    pub moderation_reason: Option<String>,
+}
+
+impl Joy {
+    pub fn is_editable(&self) -> bool {
+        OffsetDateTime::now_utc() - self.created < EDIT_WINDOW
+    }
+
+    /// Drops what only the owner may see when someone else is viewing.
+    fn seen_by(self, viewer: &Uuid) -> Joy {
+        if self.user_id == Some(*viewer) {
+            return self;
+        }
+        Joy {
+            longitude: self.longitude.map(coarse),
+            latitude: self.latitude.map(coarse),
+            accuracy: None,
+            ..self
+        }
+    }
+}
+
+/// Rounds a coordinate to two decimal places, roughly a kilometre, so other
+/// users' joys cannot be traced to a doorstep.
+fn coarse(degrees: f64) -> f64 {
+    (degrees * 100.0).round() / 100.0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Reaction {
+    Smile,
+    Relate,
+    Hug,
+}
+
+impl Reaction {
+    pub const ALL: [Reaction; 3] = [Reaction::Smile, Reaction::Relate, Reaction::Hug];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Reaction::Smile => "smile",
+            Reaction::Relate => "relate",
+            Reaction::Hug => "hug",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Reaction::Smile => "😊",
+            Reaction::Relate => "🤝",
+            Reaction::Hug => "🤗",
+        }
+    }
+
+    /// Catalog id of the reaction's tooltip.
+    pub fn label_id(&self) -> &'static str {
+        match self {
+            Reaction::Smile => "reaction-smile",
+            Reaction::Relate => "reaction-relate",
+            Reaction::Hug => "reaction-hug",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct Reactions {
+    pub smile: i64,
+    pub relate: i64,
+    pub hug: i64,
+}
+
+impl Reactions {
+    pub fn count(&self, reaction: &Reaction) -> i64 {
+        match reaction {
+            Reaction::Smile => self.smile,
+            Reaction::Relate => self.relate,
+            Reaction::Hug => self.hug,
+        }
+    }
+
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            smile: row.get::<i64, _>("smile"),
+            relate: row.get::<i64, _>("relate"),
+            hug: row.get::<i64, _>("hug"),
+        }
+    }
+}
+
+fn status_from_row(row: &PgRow) -> Status {
+    Status::parse(&row.get::<String, _>("status")).unwrap_or(Status::Held)
+}
+
+fn reaction_from_row(row: &PgRow) -> Option<Reaction> {
+    row.get::<Option<String>, _>("reaction")
+        .as_deref()
+        .and_then(Reaction::parse)
+}
+
+/// A window onto a listing. Without a limit, everything from `offset` on.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Page {
+    pub limit: Option<i64>,
+    pub offset: i64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NewJoy {
+    pub frustration: String,
+    pub context: String,
+    pub joy: String,
+    /// Where the joy was written, if the client knows.
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct JoyUpdate {
+    pub frustration: Option<String>,
+    pub context: Option<String>,
+    pub joy: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct JoyService {
     db: PgPool,
+    moderation: Arc<ModerationService>,
+    gazetteer: Arc<Gazetteer>,
 }
 
 impl JoyService {
-    pub fn new(db: PgPool) -> Self {
-        Self { db }
+    pub fn new(db: PgPool, moderation: Arc<ModerationService>, gazetteer: Arc<Gazetteer>) -> Self {
+        Self { db, moderation, gazetteer }
+    }
+
+    fn labelled(&self, joy: Joy) -> Joy {
+        let place = joy.latitude
+            .zip(joy.longitude)
+            .and_then(|(lat, lon)| self.gazetteer.label(lat, lon));
+        Joy { place, ..joy }
     }
 
     fn validate(&self, frustration: &str, context: &str, joy: &str) -> Result<(), String> {
@@ -41,7 +225,9 @@ impl JoyService {
         Ok(())
     }
 
-    pub async fn list_for_user(&self, user_id: &Uuid) -> Result<Vec<Joy>, String> {
+    /// The feed as seen by `user_id`: joys within their radius if they have
+    /// a location, otherwise the last 24 hours, ordered by their ranking.
+    pub async fn list_for_user(&self, user_id: &Uuid, preferences: &Preferences, page: Page) -> Result<Vec<Joy>, String> {
         let row = sqlx::query(r#"
             SELECT point IS NOT NULL AS has_point
             FROM users WHERE id = $1
@@ -53,48 +239,107 @@ impl JoyService {
 
         let has_point = row.get::<bool, _>("has_point");
 
+        let order = match (preferences.ranking, has_point) {
+            (Ranking::Nearest, true) => "ST_Distance(j.point, u.point) ASC",
+            (Ranking::Nearest, false) | (Ranking::Newest, _) => "j.created DESC",
+            (Ranking::Popular, _) => "(r.smile + r.relate + r.hug) DESC, j.created DESC",
+        };
+
         let rows = if has_point {
-            sqlx::query(
+            sqlx::query(&format!(
                 r#"
                     SELECT
                         j.id,
                         j.user_id,
                         j.created,
+                        j.updated,
                         ST_X(j.point::geometry) AS longitude,
                         ST_Y(j.point::geometry) AS latitude,
+                        j.accuracy,
                         j.joy,
-                        ST_DISTANCE(j.point, u.point) AS distance
+                        j.status,
+                        ST_DISTANCE(j.point, u.point) AS distance,
+                        r.smile,
+                        r.relate,
+                        r.hug,
+                        r.reaction
                     FROM joys j
                     JOIN users u ON u.id = $1
+                    CROSS JOIN LATERAL (
+                        SELECT
+                            COUNT(*) FILTER (WHERE kind = 'smile') AS smile,
+                            COUNT(*) FILTER (WHERE kind = 'relate') AS relate,
+                            COUNT(*) FILTER (WHERE kind = 'hug') AS hug,
+                            MAX(kind) FILTER (WHERE user_id = $1) AS reaction
+                        FROM joy_reactions
+                        WHERE joy_id = j.id
+                    ) r
                     WHERE u.point IS NOT NULL
+                        AND j.deleted IS NULL
+                        AND (j.status = 'approved' OR (j.status = 'held' AND j.user_id = $1))
+                        AND NOT EXISTS (
+                            SELECT 1 FROM user_hides h
+                            WHERE h.user_id = $1 AND (h.joy_id = j.id OR h.author_id = j.user_id)
+                        )
                         AND j.point IS NOT NULL
-                        AND ST_DWithin(j.point, u.point, 1000000)
-                    ORDER BY ST_Distance(j.point, u.point) ASC
+                        AND ST_DWithin(j.point, u.point, $2)
+                    ORDER BY {}
+                    LIMIT $3 OFFSET $4
                 "#,
-            )
+                order,
+            ))
                 .bind(user_id)
+                .bind(preferences.radius)
+                .bind(page.limit)
+                .bind(page.offset)
                 .fetch_all(&self.db)
                 .await
                 .map_err(|e| e.to_string())?
         } else {
-            sqlx::query(
+            sqlx::query(&format!(
                 r#"
                     SELECT
                         j.id,
                         j.user_id,
                         j.created,
+                        j.updated,
                         ST_X(j.point::geometry) AS longitude,
                         ST_Y(j.point::geometry) AS latitude,
+                        j.accuracy,
                         j.joy,
-                        NULL AS distance
+                        j.status,
+                        NULL AS distance,
+                        r.smile,
+                        r.relate,
+                        r.hug,
+                        r.reaction
                     FROM joys j
                     JOIN users u ON u.id = $1
+                    CROSS JOIN LATERAL (
+                        SELECT
+                            COUNT(*) FILTER (WHERE kind = 'smile') AS smile,
+                            COUNT(*) FILTER (WHERE kind = 'relate') AS relate,
+                            COUNT(*) FILTER (WHERE kind = 'hug') AS hug,
+                            MAX(kind) FILTER (WHERE user_id = $1) AS reaction
+                        FROM joy_reactions
+                        WHERE joy_id = j.id
+                    ) r
                     WHERE u.point IS NULL
+                        AND j.deleted IS NULL
+                        AND (j.status = 'approved' OR (j.status = 'held' AND j.user_id = $1))
+                        AND NOT EXISTS (
+                            SELECT 1 FROM user_hides h
+                            WHERE h.user_id = $1 AND (h.joy_id = j.id OR h.author_id = j.user_id)
+                        )
                         AND j.created >= (NOW() - INTERVAL '24 hour')
-                    ORDER BY j.created DESC
+                    ORDER BY {}
+                    LIMIT $2 OFFSET $3
                 "#,
-            )
+                order,
+            ))
                 .bind(user_id)
+                .bind(page.limit)
+                .bind(page.offset)
                 .fetch_all(&self.db)
                 .await
                 .map_err(|e| e.to_string())?
@@ -102,36 +347,144 @@ impl JoyService {
 
         let joys = rows
             .into_iter()
-            .map(|row| Joy {
+            .map(|row| self.labelled(Joy {
                 id: row.get::<Uuid, _>("id"),
-                user_id: row.get::<Uuid, _>("user_id"),
+                user_id: row.get::<Option<Uuid>, _>("user_id"),
                 created: row.get::<OffsetDateTime, _>("created"),
+                updated: row.get::<Option<OffsetDateTime>, _>("updated"),
                 longitude: row.get::<Option<f64>, _>("longitude"),
                 latitude: row.get::<Option<f64>, _>("latitude"),
+                accuracy: row.get::<Option<f64>, _>("accuracy"),
                 frustration: None,
                 context: None,
                 joy: row.get::<String, _>("joy"),
                 distance: row.get::<Option<f64>, _>("distance"),
-            })
+                place: None,
+                reactions: Reactions::from_row(&row),
+                reaction: reaction_from_row(&row),
+                status: status_from_row(&row),
+                moderation_reason: None,
+            }).seen_by(user_id))
             .collect();
 
         Ok(joys)
     }
 
+    /// The user's own joys, newest first, including private fields.
+    pub async fn list_own(&self, user_id: &Uuid, limit: i64) -> Result<Vec<Joy>, String> {
+        let rows = sqlx::query(r#"
+            SELECT
+                id,
+                user_id,
+                created,
+                updated,
+                ST_X(point::geometry) AS longitude,
+                ST_Y(point::geometry) AS latitude,
+                accuracy,
+                frustration,
+                context,
+                joy,
+                status,
+                moderation_reason
+            FROM joys
+            WHERE user_id = $1 AND deleted IS NULL
+            ORDER BY created DESC
+            LIMIT $2
+        "#)
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| self.labelled(Joy {
+                id: row.get::<Uuid, _>("id"),
+                user_id: row.get::<Option<Uuid>, _>("user_id"),
+                created: row.get::<OffsetDateTime, _>("created"),
+                updated: row.get::<Option<OffsetDateTime>, _>("updated"),
+                longitude: row.get::<Option<f64>, _>("longitude"),
+                latitude: row.get::<Option<f64>, _>("latitude"),
+                accuracy: row.get::<Option<f64>, _>("accuracy"),
+                frustration: row.get::<Option<String>, _>("frustration"),
+                context: row.get::<Option<String>, _>("context"),
+                joy: row.get::<String, _>("joy"),
+                distance: None,
+                place: None,
+                reactions: Reactions::default(),
+                reaction: None,
+                status: status_from_row(&row),
+                moderation_reason: row.get::<Option<String>, _>("moderation_reason"),
+            }))
+            .collect())
+    }
+
     pub async fn create(
         &self,
         user_id: &Uuid,
         frustration: String,
         context: String,
         joy: String,
+    ) -> Result<Joy, String> {
+        self.insert(&self.db, user_id, frustration, context, joy).await
+    }
+
+    /// Turns the user's joy form draft into a joy. The draft is deleted in
+    /// the same transaction, so two submits of one draft post it once, and a
+    /// draft that fails validation is kept. Returns `None` without a draft.
+    pub async fn create_from_draft(&self, user_id: &Uuid) -> Result<Option<Joy>, String> {
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        let draft = sqlx::query(r#"
+            DELETE FROM joy_drafts
+            WHERE user_id = $1
+            RETURNING frustration, context, joy
+        "#)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(draft) = draft else {
+            return Ok(None);
+        };
+
+        let joy = self.insert(
+            &mut *tx,
+            user_id,
+            draft.get::<String, _>("frustration"),
+            draft.get::<String, _>("context"),
+            draft.get::<String, _>("joy"),
+        ).await?;
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(Some(joy))
+    }
+
+    async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+        &self,
+        executor: E,
+        user_id: &Uuid,
+        frustration: String,
+        context: String,
+        joy: String,
     ) -> Result<Joy, String> {
         self.validate(&frustration, &context, &joy)?;
 
+        let verdict = self.moderation.review(&[frustration.trim(), context.trim(), joy.trim()]);
+
         let row = sqlx::query(r#"
-            INSERT INTO joys (user_id, point, frustration, context, joy, created)
-            SELECT id, point, $1, $2, $3, NOW()
-            FROM users WHERE id = $4
-            RETURNING id, user_id, frustration, context, joy, created,
+            INSERT INTO joys (user_id, point, accuracy, frustration, context, joy, created, status, moderation_reason)
+            SELECT
+                u.id,
+                CASE WHEN COALESCE(p.share_location, TRUE) THEN u.point END,
+                CASE WHEN COALESCE(p.share_location, TRUE) THEN u.accuracy END,
+                $1, $2, $3, NOW(), $5, $6
+            FROM users u
+            LEFT JOIN user_preferences p ON p.user_id = u.id
+            WHERE u.id = $4
+            RETURNING id, user_id, frustration, context, joy, created, status, moderation_reason, accuracy,
               ST_X(point::geometry) AS longitude,
               ST_Y(point::geometry) AS latitude
         "#)
@@ -139,39 +492,218 @@ impl JoyService {
             .bind(context.trim())
             .bind(joy.trim())
             .bind(user_id)
-            .fetch_one(&self.db)
+            .bind(verdict.status.as_str())
+            .bind(verdict.reason())
+            .fetch_one(executor)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(self.labelled(Joy {
+            id: row.get::<Uuid, _>("id"),
+            user_id: row.get::<Option<Uuid>, _>("user_id"),
+            created: row.get::<OffsetDateTime, _>("created"),
+            updated: None,
+            longitude: row.get::<Option<f64>, _>("longitude"),
+            latitude: row.get::<Option<f64>, _>("latitude"),
+            accuracy: row.get::<Option<f64>, _>("accuracy"),
+            frustration: row.get::<Option<String>, _>("frustration"),
+            context: row.get::<Option<String>, _>("context"),
+            joy: row.get::<String, _>("joy"),
+            distance: Some(0f64),
+            place: None,
+            reactions: Reactions::default(),
+            reaction: None,
+            status: status_from_row(&row),
+            moderation_reason: row.get::<Option<String>, _>("moderation_reason"),
+        }))
+    }
+
+    /// Applies the given changes to a joy owned by `user_id`, provided it has
+    /// not been deleted and is still inside the edit window. Returns `None`
+    /// when no such joy exists.
+    pub async fn update(&self, id: &Uuid, user_id: &Uuid, changes: JoyUpdate) -> Result<Option<Joy>, String> {
+        let trimmed = |value: &Option<String>| value.as_deref().map(str::trim).map(str::to_string);
+        let frustration = trimmed(&changes.frustration);
+        let context = trimmed(&changes.context);
+        let joy = trimmed(&changes.joy);
+        for (name, value) in [("frustration", &frustration), ("context", &context), ("joy", &joy)] {
+            if value.as_deref() == Some("") {
+                return Err(format!("{} cannot be empty", name));
+            }
+        }
+
+        let current = sqlx::query(r#"
+            SELECT frustration, context, joy
+            FROM joys
+            WHERE id = $1
+                AND user_id = $2
+                AND deleted IS NULL
+                AND created >= NOW() - make_interval(secs => $3)
+        "#)
+            .bind(id)
+            .bind(user_id)
+            .bind(EDIT_WINDOW.as_seconds_f64())
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(current) = current else {
+            return Ok(None);
+        };
+
+        let frustration = frustration.unwrap_or_else(|| current.get::<String, _>("frustration"));
+        let context = context.unwrap_or_else(|| current.get::<String, _>("context"));
+        let joy = joy.unwrap_or_else(|| current.get::<String, _>("joy"));
+
+        let verdict = self.moderation.review(&[&frustration, &context, &joy]);
+        if verdict.status == Status::Rejected {
+            return Err(verdict.reason().unwrap_or_else(|| "joy was rejected".to_string()));
+        }
+
+        let row = sqlx::query(r#"
+            UPDATE joys SET
+                frustration = $3,
+                context = $4,
+                joy = $5,
+                status = $7,
+                moderation_reason = $8,
+                updated = NOW()
+            WHERE id = $1
+                AND user_id = $2
+                AND deleted IS NULL
+                AND created >= NOW() - make_interval(secs => $6)
+            RETURNING id, user_id, frustration, context, joy, created, updated, status, moderation_reason, accuracy,
+              ST_X(point::geometry) AS longitude,
+              ST_Y(point::geometry) AS latitude
+        "#)
+            .bind(id)
+            .bind(user_id)
+            .bind(frustration)
+            .bind(context)
+            .bind(joy)
+            .bind(EDIT_WINDOW.as_seconds_f64())
+            .bind(verdict.status.as_str())
+            .bind(verdict.reason())
+            .fetch_optional(&self.db)
             .await
             .map_err(|e| e.to_string())?;
 
-        Ok(Joy {
+        Ok(row.map(|row| self.labelled(Joy {
             id: row.get::<Uuid, _>("id"),
-            user_id: row.get::<Uuid, _>("user_id"),
+            user_id: row.get::<Option<Uuid>, _>("user_id"),
             created: row.get::<OffsetDateTime, _>("created"),
+            updated: row.get::<Option<OffsetDateTime>, _>("updated"),
             longitude: row.get::<Option<f64>, _>("longitude"),
             latitude: row.get::<Option<f64>, _>("latitude"),
+            accuracy: row.get::<Option<f64>, _>("accuracy"),
             frustration: row.get::<Option<String>, _>("frustration"),
             context: row.get::<Option<String>, _>("context"),
             joy: row.get::<String, _>("joy"),
             distance: Some(0f64),
-        })
+            place: None,
+            reactions: Reactions::default(),
+            reaction: None,
+            status: status_from_row(&row),
+            moderation_reason: row.get::<Option<String>, _>("moderation_reason"),
+        })))
+    }
+
+    /// Toggles `user_id`'s reaction on a joy: reacting with the same kind
+    /// again removes it, reacting with a different kind replaces it.
+    pub async fn react(&self, id: &Uuid, user_id: &Uuid, reaction: Reaction) -> Result<(), String> {
+        sqlx::query(r#"
+            WITH removed AS (
+                DELETE FROM joy_reactions
+                WHERE joy_id = $1 AND user_id = $2 AND kind = $3
+                RETURNING 1
+            )
+            INSERT INTO joy_reactions (joy_id, user_id, kind)
+            SELECT $1, $2, $3
+            WHERE NOT EXISTS (SELECT 1 FROM removed)
+            ON CONFLICT (joy_id, user_id) DO UPDATE SET kind = EXCLUDED.kind, created = NOW()
+        "#)
+            .bind(id)
+            .bind(user_id)
+            .bind(reaction.as_str())
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    pub async fn reactions(&self, id: &Uuid, user_id: &Uuid) -> Result<(Reactions, Option<Reaction>), String> {
+        let row = sqlx::query(r#"
+            SELECT
+                COUNT(*) FILTER (WHERE kind = 'smile') AS smile,
+                COUNT(*) FILTER (WHERE kind = 'relate') AS relate,
+                COUNT(*) FILTER (WHERE kind = 'hug') AS hug,
+                MAX(kind) FILTER (WHERE user_id = $2) AS reaction
+            FROM joy_reactions
+            WHERE joy_id = $1
+        "#)
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((Reactions::from_row(&row), reaction_from_row(&row)))
+    }
+
+    /// Soft-deletes a joy owned by `user_id`. Returns whether anything was deleted.
+    pub async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, String> {
+        let result = sqlx::query(r#"
+            UPDATE joys SET deleted = NOW()
+            WHERE id = $1 AND user_id = $2 AND deleted IS NULL
+        "#)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.rows_affected() > 0)
     }
 
     pub async fn get_for_user(&self, id: Uuid, user_id: Uuid) -> Result<Option<Joy>, String> {
         let row = sqlx::query(
             r#"
                 SELECT
-                    id,
-                    user_id,
-                    created,
-                    ST_X(point::geometry) AS longitude,
-                    ST_Y(point::geometry) AS latitude,
-                    joy,
+                    j.id,
+                    j.user_id,
+                    j.created,
+                    j.updated,
+                    ST_X(j.point::geometry) AS longitude,
+                    ST_Y(j.point::geometry) AS latitude,
+                    j.accuracy,
+                    j.joy,
+                    j.status,
                     ST_Distance(
-                        point,
+                        j.point,
                         (SELECT point FROM users WHERE id = $2)
-                    ) AS distance
-                FROM joys
-                WHERE id = $1
+                    ) AS distance,
+                    r.smile,
+                    r.relate,
+                    r.hug,
+                    r.reaction
+                FROM joys j
+                CROSS JOIN LATERAL (
+                    SELECT
+                        COUNT(*) FILTER (WHERE kind = 'smile') AS smile,
+                        COUNT(*) FILTER (WHERE kind = 'relate') AS relate,
+                        COUNT(*) FILTER (WHERE kind = 'hug') AS hug,
+                        MAX(kind) FILTER (WHERE user_id = $2) AS reaction
+                    FROM joy_reactions
+                    WHERE joy_id = j.id
+                ) r
+                WHERE j.id = $1
+                    AND j.deleted IS NULL
+                    AND (j.status = 'approved' OR (j.status = 'held' AND j.user_id = $2))
+                    AND NOT EXISTS (
+                        SELECT 1 FROM user_hides h
+                        WHERE h.user_id = $2 AND (h.joy_id = j.id OR h.author_id = j.user_id)
+                    )
             "#,
         )
             .bind(id)
@@ -180,16 +712,193 @@ impl JoyService {
             .await
             .map_err(|e| e.to_string())?;
 
-        Ok(row.map(|row| Joy {
+        Ok(row.map(|row| self.labelled(Joy {
             id: row.get::<Uuid, _>("id"),
-            user_id: row.get::<Uuid, _>("user_id"),
+            user_id: row.get::<Option<Uuid>, _>("user_id"),
             longitude: row.get::<Option<f64>, _>("longitude"),
             latitude: row.get::<Option<f64>, _>("latitude"),
+            accuracy: row.get::<Option<f64>, _>("accuracy"),
             frustration: None,
             context: None,
             joy: row.get::<String, _>("joy"),
             created: row.get::<OffsetDateTime, _>("created"),
+            updated: row.get::<Option<OffsetDateTime>, _>("updated"),
             distance: row.get::<Option<f64>, _>("distance"),
-        }))
+            place: None,
+            reactions: Reactions::from_row(&row),
+            reaction: reaction_from_row(&row),
+            status: status_from_row(&row),
+            moderation_reason: None,
+        }).seen_by(&user_id)))
     }
 }
+
+/// Posts a joy for `user`: remembers where it was written if they share
+/// their location, stores it after moderation and tells subscribers.
+/// Rejected joys are returned as an error.
+pub async fn submit(state: &AppState, user: &User, form: NewJoy) -> Result<Joy, (StatusCode, String)> {
+    prepare(state, user, form.longitude, form.latitude, form.accuracy).await?;
+
+    let joy = state
+        .joys
+        .create(&user.id, form.frustration, form.context, form.joy)
+        .await;
+    posted(state, user, joy)
+}
+
+/// Posts the user's joy form draft, consuming it. Fails with 409 if there
+/// is no draft, e.g. because it was already submitted.
+pub async fn submit_draft(state: &AppState, user: &User, location: DraftLocation) -> Result<Joy, (StatusCode, String)> {
+    prepare(state, user, location.longitude, location.latitude, location.accuracy).await?;
+
+    let joy = match state.joys.create_from_draft(&user.id).await {
+        Ok(Some(joy)) => Ok(joy),
+        Ok(None) => return Err((StatusCode::CONFLICT, "nothing to submit".to_string())),
+        Err(e) => Err(e),
+    };
+    posted(state, user, joy)
+}
+
+/// Where the draft is being posted from, if the client knows.
+pub struct DraftLocation {
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+    pub accuracy: Option<f64>,
+}
+
+async fn prepare(
+    state: &AppState,
+    user: &User,
+    longitude: Option<f64>,
+    latitude: Option<f64>,
+    accuracy: Option<f64>,
+) -> Result<(), (StatusCode, String)> {
+    if user.banned {
+        return Err((StatusCode::FORBIDDEN, "you have been banned from posting".to_string()));
+    }
+
+    let preferences = state.preferences.get(&user.id).await
+        .map_err(crate::service::internal_error)?;
+
+    if let (true, Some(lon), Some(lat)) = (preferences.share_location, longitude, latitude) {
+        // persist the last known location for this user
+        if let Err(e) = state.users.update_location(&user.id, lon, lat, accuracy).await {
+            tracing::warn!(error = %e, "failed to update user location");
+        }
+    }
+
+    Ok(())
+}
+
+fn posted(state: &AppState, user: &User, joy: Result<Joy, String>) -> Result<Joy, (StatusCode, String)> {
+    let joy = joy.map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+
+    if joy.status == Status::Rejected {
+        let reason = joy.moderation_reason.unwrap_or_else(|| "joy was rejected".to_string());
+        return Err((StatusCode::BAD_REQUEST, reason));
+    }
+
+    // Held joys are only visible to their author until reviewed.
+    let user_id = match joy.status {
+        Status::Approved => None,
+        _ => Some(user.id),
+    };
+    if let Err(e) = state.sse.publish(Patch::JoyCards { user_id }) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(joy)
+}
+
+async fn owned_joy(state: &AppState, id: Uuid, user_id: Uuid) -> Result<Joy, (StatusCode, String)> {
+    let joy = state.joys.get_for_user(id, user_id).await
+        .map_err(crate::service::internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "joy not found".to_string()))?;
+
+    if joy.user_id != Some(user_id) {
+        return Err((StatusCode::FORBIDDEN, "not your joy".to_string()));
+    }
+
+    Ok(joy)
+}
+
+pub async fn update_joy(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    locale: Locale,
+    Path(id): Path<Uuid>,
+    Json(form): Json<JoyUpdate>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    if user.banned {
+        return Err((StatusCode::FORBIDDEN, "you have been banned from posting".to_string()));
+    }
+
+    let joy = owned_joy(&state, id, user.id).await?;
+    if !joy.is_editable() {
+        return Err((StatusCode::FORBIDDEN, "edit window has closed".to_string()));
+    }
+
+    state.joys.update(&id, &user.id, form).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .ok_or_else(|| (StatusCode::CONFLICT, "joy can no longer be edited".to_string()))?;
+
+    let card = JoyCard::build(&state, &user, locale, JoyCardProps { id })
+        .await?
+        .fragment()
+        .map_err(crate::service::internal_error)?;
+
+    // Subscribers who can no longer see a held edit get a removal instead.
+    if let Err(e) = state.sse.publish(Patch::JoyCard(id)) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(card)
+}
+
+pub async fn delete_joy(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    owned_joy(&state, id, user.id).await?;
+
+    if !state.joys.delete(&id, &user.id).await.map_err(crate::service::internal_error)? {
+        return Err((StatusCode::NOT_FOUND, "joy not found".to_string()));
+    }
+
+    if let Err(e) = state.sse.publish(Patch::Remove(JoyCard::dom_id(&id))) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn react_to_joy(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Path((id, reaction)): Path<(Uuid, Reaction)>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    state.joys.get_for_user(id, user.id).await
+        .map_err(crate::service::internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "joy not found".to_string()))?;
+
+    state.joys.react(&id, &user.id, reaction).await
+        .map_err(crate::service::internal_error)?;
+
+    let (reactions, reaction) = state.joys.reactions(&id, &user.id).await
+        .map_err(crate::service::internal_error)?;
+
+    let selector = format!("#{}", JoyCard::dom_id(&id));
+    let broadcast = Patch::Signals {
+        selector: Some(selector.clone()),
+        signals: json!({ "reactions": reactions }).to_string(),
+    };
+    if let Err(e) = state.sse.publish(broadcast) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok((
+        [("datastar-selector", selector)],
+        Json(json!({ "reactions": reactions, "reaction": reaction })),
+    ))
+}
@@ -0,0 +1,95 @@
+use unic_langid::LanguageIdentifier;
+
+/// A display locale for server-rendered strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    EnUs,
+    EnGb,
+    Fr,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::EnUs, Locale::EnGb, Locale::Fr];
+
+    /// The BCP 47 tag, as understood by `Intl` on the client.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::EnUs => "en-US",
+            Locale::EnGb => "en-GB",
+            Locale::Fr => "fr",
+        }
+    }
+
+    /// The locale's own name for itself, for the language switcher.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Locale::EnUs => "English (US)",
+            Locale::EnGb => "English (UK)",
+            Locale::Fr => "Français",
+        }
+    }
+
+    pub fn parse(tag: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|l| l.tag().eq_ignore_ascii_case(tag.trim()))
+    }
+
+    pub fn langid(&self) -> LanguageIdentifier {
+        self.tag().parse().expect("invalid locale tag")
+    }
+
+    /// Units to use until the user picks their own.
+    pub fn default_units(&self) -> Units {
+        match self {
+            Locale::EnUs | Locale::EnGb => Units::Imperial,
+            Locale::Fr => Units::Metric,
+        }
+    }
+
+    /// Formats a number with `digits` decimal places and the locale's
+    /// decimal separator.
+    pub fn decimal(&self, value: f64, digits: usize) -> String {
+        let s = format!("{:.*}", digits, value);
+        match self {
+            Locale::Fr => s.replace('.', ","),
+            _ => s,
+        }
+    }
+}
+
+/// Measurement system for distances.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Units {
+    Metric,
+    #[default]
+    Imperial,
+}
+
+impl Units {
+    pub const ALL: [Units; 2] = [Units::Metric, Units::Imperial];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Units::Metric => "metric",
+            Units::Imperial => "imperial",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "metric" => Some(Units::Metric),
+            "imperial" => Some(Units::Imperial),
+            _ => None,
+        }
+    }
+
+    /// Catalog id of the unit system's name.
+    pub fn label_id(&self) -> &'static str {
+        match self {
+            Units::Metric => "units-metric",
+            Units::Imperial => "units-imperial",
+        }
+    }
+}
@@ -2,6 +2,7 @@
 interface LocationPayload {
     latitude: number;
     longitude: number;
+    accuracy: number;
 }
 
 export class LocationService {
@@ -36,18 +37,19 @@ export class LocationService {
     }
 
     private success(position: GeolocationPosition): void {
-        const { latitude, longitude } = position.coords;
-        this.ping(latitude, longitude);
+        const { latitude, longitude, accuracy } = position.coords;
+        this.ping(latitude, longitude, accuracy);
     }
 
-    private async ping(latitude: number, longitude: number): Promise<void> {
-        const payload: LocationPayload = { latitude, longitude };
+    private async ping(latitude: number, longitude: number, accuracy: number): Promise<void> {
+        const payload: LocationPayload = { latitude, longitude, accuracy };
 
         try {
             const response = await fetch(this.ENDPOINT, {
                 method: 'POST',
                 headers: {
                     'Content-Type': 'application/json',
+                    'X-CSRF-Token': document.querySelector<HTMLMetaElement>('meta[name="csrf-token"]')?.content ?? '',
                 },
                 // IMPORTANT: Ensures the browser sends the 'session_id' cookie
                 credentials: 'include',
@@ -1,8 +1,28 @@
+pub mod admin;
+pub mod auth;
+pub mod csrf;
+pub mod date;
+pub mod debounce;
+pub mod distance;
+pub mod draft;
 pub mod event;
+pub mod gazetteer;
+pub mod i18n;
 pub mod sse;
 pub mod state;
 pub mod user;
 pub mod joy;
+pub mod locale;
+pub mod moderation;
+pub mod preferences;
+pub mod privacy;
+pub mod rate_limit;
+pub mod report;
+pub mod retention;
+pub mod security;
+pub mod session;
+pub mod tls;
+pub mod token;
 
 use axum::http::StatusCode;
 
@@ -0,0 +1,290 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+const DEFAULT_BLOCKLIST: &str = include_str!("../../moderation/blocklist.txt");
+
+/// Moderation outcome for a joy. Ordered by severity so the strictest
+/// verdict across all checks wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Approved,
+    Held,
+    Rejected,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Approved => "approved",
+            Status::Held => "held",
+            Status::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "approved" => Some(Status::Approved),
+            "held" => Some(Status::Held),
+            "rejected" => Some(Status::Rejected),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Flag {
+    pub check: &'static str,
+    pub status: Status,
+    pub reason: String,
+}
+
+pub trait Check: Send + Sync {
+    fn check(&self, text: &str) -> Option<Flag>;
+}
+
+/// Rejects text containing any listed word or phrase.
+pub struct Blocklist {
+    phrases: HashSet<String>,
+}
+
+impl Blocklist {
+    pub fn parse(source: &str) -> Self {
+        let phrases = source
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(normalise)
+            .collect();
+        Self { phrases }
+    }
+
+    /// Loads the blocklist at `path`, falling back to the bundled default.
+    pub fn load(path: Option<String>) -> Result<Self, String> {
+        match path {
+            Some(path) => std::fs::read_to_string(&path)
+                .map(|s| Self::parse(&s))
+                .map_err(|e| format!("failed to read blocklist {}: {}", path, e)),
+            None => Ok(Self::parse(DEFAULT_BLOCKLIST)),
+        }
+    }
+}
+
+/// Lowercases and collapses text to single-space-separated words, padded
+/// with spaces so phrases can be matched on word boundaries.
+fn normalise(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+impl Check for Blocklist {
+    fn check(&self, text: &str) -> Option<Flag> {
+        let text = normalise(text);
+        self.phrases
+            .iter()
+            .find(|phrase| text.contains(phrase.as_str()))
+            .map(|phrase| Flag {
+                check: "blocklist",
+                status: Status::Rejected,
+                reason: format!("contains blocked phrase \"{}\"", phrase.trim()),
+            })
+    }
+}
+
+/// Flags text matching a pattern, e.g. personal information or links.
+pub struct PatternCheck {
+    name: &'static str,
+    status: Status,
+    pattern: Regex,
+}
+
+impl PatternCheck {
+    pub fn new(name: &'static str, status: Status, pattern: &str) -> Self {
+        Self {
+            name,
+            status,
+            pattern: Regex::new(pattern).expect("invalid moderation pattern"),
+        }
+    }
+
+    pub fn email() -> Self {
+        Self::new("email", Status::Held, r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b")
+    }
+
+    /// International numbers with a `+` prefix, national ones with a
+    /// leading trunk `0`, and North American `(555) 123-4567` groupings.
+    /// Bare runs of digits, like years or scores, don't count.
+    pub fn phone() -> Self {
+        Self::new(
+            "phone",
+            Status::Held,
+            concat!(
+                r"\+\d{1,3}[\s.-]?(?:\(\d{1,4}\)[\s.-]?)?\d{2,4}(?:[\s.-]?\d{2,4}){2,4}\b",
+                r"|\b0\d{2,4}[\s.-]?\d{3,4}[\s.-]?\d{3,4}\b",
+                r"|\b0\d(?:[\s.-]?\d{2}){4}\b",
+                r"|(?:\(\d{3}\)\s?|\b\d{3}[.-])\d{3}[.-]\d{4}\b",
+            ),
+        )
+    }
+
+    /// A house number followed by a capitalised street name and type, as in
+    /// "221B Baker Street", or a UK postcode. Case-sensitive, so distances
+    /// like "5 km down the road" don't match.
+    pub fn address() -> Self {
+        Self::new(
+            "address",
+            Status::Held,
+            concat!(
+                r"\b\d{1,5}[A-Za-z]?\s+(?:[A-Z][a-z]+\s+){1,3}",
+                r"(?:Street|St|Road|Rd|Avenue|Ave|Lane|Ln|Drive|Dr|Boulevard|Blvd|Court|Ct|Close|Way|Place|Pl)\b",
+                r"|\b[A-Z]{1,2}\d[A-Z\d]?\s*\d[A-Z]{2}\b",
+            ),
+        )
+    }
+
+    pub fn url() -> Self {
+        Self::new(
+            "url",
+            Status::Held,
+            r"(?i)\b(?:https?://|www\.)\S+|\b[a-z0-9-]+\.(?:com|net|org|io|co|uk|info|biz|xyz|me|app|dev)\b",
+        )
+    }
+}
+
+impl Check for PatternCheck {
+    fn check(&self, text: &str) -> Option<Flag> {
+        self.pattern.find(text).map(|_| Flag {
+            check: self.name,
+            status: self.status,
+            reason: format!("looks like it contains {}", self.name),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Verdict {
+    pub status: Status,
+    pub flags: Vec<Flag>,
+}
+
+impl Verdict {
+    pub fn reason(&self) -> Option<String> {
+        if self.flags.is_empty() {
+            return None;
+        }
+        let reasons: Vec<String> = self.flags
+            .iter()
+            .map(|f| format!("{}: {}", f.check, f.reason))
+            .collect();
+        Some(reasons.join("; "))
+    }
+}
+
+pub struct ModerationService {
+    checks: Vec<Box<dyn Check>>,
+}
+
+impl ModerationService {
+    pub fn new(checks: Vec<Box<dyn Check>>) -> Self {
+        Self { checks }
+    }
+
+    pub fn with_default_checks(blocklist: Blocklist) -> Self {
+        Self::new(vec![
+            Box::new(blocklist),
+            Box::new(PatternCheck::email()),
+            Box::new(PatternCheck::phone()),
+            Box::new(PatternCheck::address()),
+            Box::new(PatternCheck::url()),
+        ])
+    }
+
+    /// Runs every check over every field and returns the strictest outcome.
+    pub fn review(&self, fields: &[&str]) -> Verdict {
+        let mut flags: Vec<Flag> = self.checks
+            .iter()
+            .flat_map(|check| fields.iter().filter_map(|field| check.check(field)))
+            .collect();
+        flags.dedup_by(|a, b| a.check == b.check);
+        let status = flags
+            .iter()
+            .map(|f| f.status)
+            .max()
+            .unwrap_or(Status::Approved);
+        Verdict { status, flags }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(check: &PatternCheck, text: &str) -> bool {
+        check.check(text).is_some()
+    }
+
+    #[test]
+    fn phone_matches_plausible_numbers() {
+        let phone = PatternCheck::phone();
+        assert!(flags(&phone, "call me on +44 20 7946 0958"));
+        assert!(flags(&phone, "text 07700 900123 tonight"));
+        assert!(flags(&phone, "mon numéro est 06 12 34 56 78"));
+        assert!(flags(&phone, "ring (555) 123-4567"));
+        assert!(flags(&phone, "ring 555-123-4567"));
+    }
+
+    #[test]
+    fn phone_ignores_years_and_scores() {
+        let phone = PatternCheck::phone();
+        assert!(!flags(&phone, "2024 2025 2026 were good years"));
+        assert!(!flags(&phone, "we won 3 2 1 and then 10 12 14"));
+        assert!(!flags(&phone, "ran 10000 steps in 45 minutes"));
+    }
+
+    #[test]
+    fn address_matches_street_addresses_and_postcodes() {
+        let address = PatternCheck::address();
+        assert!(flags(&address, "come round to 221B Baker Street"));
+        assert!(flags(&address, "I live at 12 Acacia Avenue"));
+        assert!(flags(&address, "near 4 Privet Dr"));
+        assert!(flags(&address, "my postcode is SW1A 1AA"));
+    }
+
+    #[test]
+    fn address_ignores_distances() {
+        let address = PatternCheck::address();
+        assert!(!flags(&address, "ran 5 km down the road"));
+        assert!(!flags(&address, "walked 3 miles along the way"));
+        assert!(!flags(&address, "took 2 wrong turns on the drive home"));
+    }
+
+    #[test]
+    fn email_and_url_match() {
+        assert!(flags(&PatternCheck::email(), "write to joy@example.com"));
+        assert!(!flags(&PatternCheck::email(), "met @ the park"));
+        assert!(flags(&PatternCheck::url(), "see https://example.org/joy"));
+        assert!(flags(&PatternCheck::url(), "it's on example.com"));
+        assert!(!flags(&PatternCheck::url(), "felt great. really great"));
+    }
+
+    #[test]
+    fn blocklist_matches_whole_words() {
+        let blocklist = Blocklist::parse("# comment\nbad word\n");
+        assert!(blocklist.check("such a BAD  word!").is_some());
+        assert!(blocklist.check("a badword").is_none());
+    }
+
+    #[test]
+    fn review_keeps_the_strictest_status() {
+        let service = ModerationService::with_default_checks(Blocklist::parse("awful"));
+        assert_eq!(service.review(&["a lovely day"]).status, Status::Approved);
+        assert_eq!(service.review(&["mail me at a@b.com"]).status, Status::Held);
+        assert_eq!(service.review(&["mail me at a@b.com", "awful"]).status, Status::Rejected);
+    }
+}
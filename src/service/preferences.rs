@@ -0,0 +1,222 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+use sqlx::{PgPool, Row};
+use time::{OffsetDateTime, UtcOffset};
+use uuid::Uuid;
+
+use crate::service::auth::CurrentUser;
+use crate::service::locale::{Locale, Units};
+use crate::service::state::AppState;
+
+/// Feed radius used until the user picks one, in metres.
+pub const DEFAULT_RADIUS: f64 = 1_000_000.0;
+
+/// Radii offered in settings, in kilometres.
+pub const RADIUS_CHOICES: [u32; 6] = [1, 5, 25, 100, 500, 1000];
+
+/// How the feed is ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Ranking {
+    /// Closest first, or newest first without a location.
+    #[default]
+    Nearest,
+    Newest,
+    /// Most reactions first.
+    Popular,
+}
+
+impl Ranking {
+    pub const ALL: [Ranking; 3] = [Ranking::Nearest, Ranking::Newest, Ranking::Popular];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ranking::Nearest => "nearest",
+            Ranking::Newest => "newest",
+            Ranking::Popular => "popular",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+
+    /// Catalog id of the ranking's name.
+    pub fn label_id(&self) -> &'static str {
+        match self {
+            Ranking::Nearest => "ranking-nearest",
+            Ranking::Newest => "ranking-newest",
+            Ranking::Popular => "ranking-popular",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Preferences {
+    /// `None` follows the locale.
+    pub units: Option<Units>,
+    /// `None` follows `Accept-Language`.
+    pub locale: Option<Locale>,
+    /// Feed radius in metres.
+    pub radius: f64,
+    pub ranking: Ranking,
+    /// Whether GPS fixes are stored and attached to new joys.
+    pub share_location: bool,
+    /// The browser's offset, as last reported; `None` renders times in UTC.
+    pub utc_offset: Option<UtcOffset>,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            units: None,
+            locale: None,
+            radius: DEFAULT_RADIUS,
+            ranking: Ranking::default(),
+            share_location: true,
+            utc_offset: None,
+        }
+    }
+}
+
+impl Preferences {
+    pub fn units_for(&self, locale: Locale) -> Units {
+        self.units.unwrap_or_else(|| locale.default_units())
+    }
+
+    /// The current time on the user's clock, as far as we know it.
+    pub fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc().to_offset(self.utc_offset.unwrap_or(UtcOffset::UTC))
+    }
+
+    pub fn utc_offset_minutes(&self) -> Option<i16> {
+        self.utc_offset.map(|offset| offset.whole_minutes())
+    }
+
+    pub fn radius_km(&self) -> f64 {
+        self.radius / 1000.0
+    }
+}
+
+pub struct PreferencesService {
+    db: PgPool,
+}
+
+impl PreferencesService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// The user's preferences, or the defaults if they have never saved any.
+    pub async fn get(&self, user_id: &Uuid) -> Result<Preferences, String> {
+        let row = sqlx::query(r#"
+            SELECT units, locale, radius, ranking, share_location, utc_offset
+            FROM user_preferences
+            WHERE user_id = $1
+        "#)
+            .bind(user_id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(row) = row else {
+            return Ok(Preferences::default());
+        };
+
+        Ok(Preferences {
+            units: row.get::<Option<String>, _>("units").as_deref().and_then(Units::parse),
+            locale: row.get::<Option<String>, _>("locale").as_deref().and_then(Locale::parse),
+            radius: row.get::<f64, _>("radius"),
+            ranking: Ranking::parse(&row.get::<String, _>("ranking")).unwrap_or_default(),
+            share_location: row.get::<bool, _>("share_location"),
+            utc_offset: row.get::<Option<i16>, _>("utc_offset").and_then(offset_from_minutes),
+        })
+    }
+
+    pub async fn save(&self, user_id: &Uuid, preferences: &Preferences) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO user_preferences (user_id, units, locale, radius, ranking, share_location, utc_offset)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (user_id) DO UPDATE SET
+                units = EXCLUDED.units,
+                locale = EXCLUDED.locale,
+                radius = EXCLUDED.radius,
+                ranking = EXCLUDED.ranking,
+                share_location = EXCLUDED.share_location,
+                utc_offset = EXCLUDED.utc_offset,
+                updated = NOW()
+        "#)
+            .bind(user_id)
+            .bind(preferences.units.map(|u| u.as_str()))
+            .bind(preferences.locale.map(|l| l.tag()))
+            .bind(preferences.radius)
+            .bind(preferences.ranking.as_str())
+            .bind(preferences.share_location)
+            .bind(preferences.utc_offset_minutes())
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Stores just the language, e.g. from the header switcher.
+    pub async fn set_locale(&self, user_id: &Uuid, locale: Locale) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO user_preferences (user_id, locale)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET locale = EXCLUDED.locale, updated = NOW()
+        "#)
+            .bind(user_id)
+            .bind(locale.tag())
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Stores just the browser's offset from UTC.
+    pub async fn set_utc_offset(&self, user_id: &Uuid, offset: UtcOffset) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO user_preferences (user_id, utc_offset)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET utc_offset = EXCLUDED.utc_offset, updated = NOW()
+        "#)
+            .bind(user_id)
+            .bind(offset.whole_minutes())
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+fn offset_from_minutes(minutes: i16) -> Option<UtcOffset> {
+    UtcOffset::from_whole_seconds(minutes as i32 * 60).ok()
+}
+
+#[derive(Deserialize)]
+pub struct TimeZone {
+    /// Minutes east of UTC, i.e. `-new Date().getTimezoneOffset()`.
+    offset: i16,
+}
+
+/// Remembers the browser's offset from UTC, so later server-rendered times
+/// match the user's clock instead of UTC.
+pub async fn set_time_zone(
+    State(state): State<AppState>,
+    CurrentUser(user): CurrentUser,
+    Json(form): Json<TimeZone>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let offset = offset_from_minutes(form.offset)
+        .filter(|offset| offset.whole_minutes().abs() <= 18 * 60)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("unsupported offset: {}", form.offset)))?;
+
+    state.preferences.set_utc_offset(&user.id, offset).await
+        .map_err(crate::service::internal_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,274 @@
+use std::io::{Cursor, Write};
+
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Form;
+use serde::Deserialize;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use tower_sessions::Session;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::service::auth::RequireAccount;
+use crate::service::sse::Patch;
+use crate::service::state::AppState;
+
+pub struct PrivacyService {
+    db: PgPool,
+}
+
+impl PrivacyService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Everything stored against the user, as one JSON object with a key
+    /// per kind of data. Includes joys the user deleted, which are kept
+    /// until the retention job removes them, and the moderation decisions
+    /// taken about the user, without naming the moderator.
+    pub async fn export(&self, user_id: &Uuid) -> Result<Value, String> {
+        let row = sqlx::query(r#"
+            SELECT json_build_object(
+                'exported', NOW(),
+                'account', (
+                    SELECT json_build_object(
+                        'id', u.id,
+                        'role', u.role,
+                        'banned', u.banned,
+                        'ban_reason', u.ban_reason,
+                        'last_seen', u.last_seen,
+                        'location_history', u.location_history,
+                        'location', CASE WHEN u.point IS NULL THEN NULL ELSE json_build_object(
+                            'longitude', ST_X(u.point::geometry),
+                            'latitude', ST_Y(u.point::geometry),
+                            'accuracy', u.accuracy,
+                            'located', u.located
+                        ) END
+                    )
+                    FROM users u WHERE u.id = $1
+                ),
+                'preferences', (
+                    SELECT json_build_object(
+                        'units', p.units,
+                        'locale', p.locale,
+                        'radius', p.radius,
+                        'ranking', p.ranking,
+                        'share_location', p.share_location,
+                        'utc_offset', p.utc_offset,
+                        'updated', p.updated
+                    )
+                    FROM user_preferences p WHERE p.user_id = $1
+                ),
+                'joys', COALESCE((
+                    SELECT json_agg(json_build_object(
+                        'id', j.id,
+                        'created', j.created,
+                        'updated', j.updated,
+                        'deleted', j.deleted,
+                        'frustration', j.frustration,
+                        'context', j.context,
+                        'joy', j.joy,
+                        'longitude', ST_X(j.point::geometry),
+                        'latitude', ST_Y(j.point::geometry),
+                        'accuracy', j.accuracy,
+                        'status', j.status,
+                        'moderation_reason', j.moderation_reason
+                    ) ORDER BY j.created)
+                    FROM joys j WHERE j.user_id = $1
+                ), '[]'::json),
+                'locations', COALESCE((
+                    SELECT json_agg(json_build_object(
+                        'created', l.created,
+                        'longitude', ST_X(l.point::geometry),
+                        'latitude', ST_Y(l.point::geometry),
+                        'accuracy', l.accuracy
+                    ) ORDER BY l.created)
+                    FROM user_locations l WHERE l.user_id = $1
+                ), '[]'::json),
+                'reactions', COALESCE((
+                    SELECT json_agg(json_build_object('joy_id', r.joy_id, 'kind', r.kind, 'created', r.created) ORDER BY r.created)
+                    FROM joy_reactions r WHERE r.user_id = $1
+                ), '[]'::json),
+                'reports', COALESCE((
+                    SELECT json_agg(json_build_object('joy_id', r.joy_id, 'reason', r.reason, 'created', r.created) ORDER BY r.created)
+                    FROM reports r WHERE r.user_id = $1
+                ), '[]'::json),
+                'hides', COALESCE((
+                    SELECT json_agg(json_build_object('joy_id', h.joy_id, 'author_id', h.author_id, 'created', h.created) ORDER BY h.created)
+                    FROM user_hides h WHERE h.user_id = $1
+                ), '[]'::json),
+                'moderation', COALESCE((
+                    SELECT json_agg(json_build_object(
+                        'action', m.action,
+                        'joy_id', m.joy_id,
+                        'note', m.note,
+                        'created', m.created
+                    ) ORDER BY m.created)
+                    FROM moderation_actions m WHERE m.user_id = $1
+                ), '[]'::json),
+                'api_tokens', COALESCE((
+                    SELECT json_agg(json_build_object(
+                        'name', t.name,
+                        'prefix', t.prefix,
+                        'scopes', t.scopes,
+                        'created', t.created,
+                        'expires', t.expires,
+                        'last_used', t.last_used
+                    ) ORDER BY t.created)
+                    FROM api_tokens t WHERE t.user_id = $1
+                ), '[]'::json),
+                'draft', (
+                    SELECT json_build_object(
+                        'step', d.step,
+                        'frustration', d.frustration,
+                        'context', d.context,
+                        'joy', d.joy,
+                        'updated', d.updated
+                    )
+                    FROM joy_drafts d WHERE d.user_id = $1
+                )
+            )::text AS export
+        "#)
+            .bind(user_id)
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        serde_json::from_str(&row.get::<String, _>("export")).map_err(|e| e.to_string())
+    }
+
+    /// Deletes the user row, which cascades to their joys and everything
+    /// else they own, and leaves a tombstone in its place. Returns `false`
+    /// if there was no such user.
+    pub async fn erase(&self, user_id: &Uuid) -> Result<bool, String> {
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        let joys = sqlx::query(r#"SELECT COUNT(*) AS joys FROM joys WHERE user_id = $1"#)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .get::<i64, _>("joys");
+
+        let deleted = sqlx::query(r#"DELETE FROM users WHERE id = $1"#)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+        if deleted == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(r#"
+            INSERT INTO user_tombstones (user_id, joys)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO NOTHING
+        "#)
+            .bind(user_id)
+            .bind(joys as i32)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    /// One JSON file per kind of data.
+    Zip,
+}
+
+#[derive(Default, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+fn attachment(content_type: &'static str, filename: String, body: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn zip(export: &Value) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let sections = export.as_object().ok_or_else(|| "export is not an object".to_string())?;
+    for (name, section) in sections {
+        zip.start_file(format!("{}.json", name), SimpleFileOptions::default())
+            .map_err(|e| e.to_string())?;
+        let json = serde_json::to_vec_pretty(section).map_err(|e| e.to_string())?;
+        zip.write_all(&json).map_err(|e| e.to_string())?;
+    }
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| e.to_string())
+}
+
+/// Downloads the user's personal data as JSON, or as a ZIP archive with
+/// `?format=zip`.
+pub async fn export_data(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let export = state.privacy.export(&user.id).await
+        .map_err(crate::service::internal_error)?;
+    let name = format!("joyus-{}", user.id);
+
+    match query.format {
+        ExportFormat::Json => {
+            let json = serde_json::to_vec_pretty(&export).map_err(crate::service::internal_error)?;
+            Ok(attachment("application/json", format!("{}.json", name), json))
+        }
+        ExportFormat::Zip => {
+            let archive = zip(&export).map_err(crate::service::internal_error)?;
+            Ok(attachment("application/zip", format!("{}.zip", name), archive))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EraseForm {
+    /// The confirmation checkbox; nothing is erased without it.
+    confirm: Option<String>,
+}
+
+/// Erases the user and ends their session. The next visit starts over as
+/// a new anonymous user.
+pub async fn erase_account(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    session: Session,
+    Form(form): Form<EraseForm>,
+) -> Result<Redirect, (StatusCode, String)> {
+    if form.confirm.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "tick the box to confirm erasure".to_string()));
+    }
+
+    if !state.privacy.erase(&user.id).await.map_err(crate::service::internal_error)? {
+        return Err((StatusCode::NOT_FOUND, "user not found".to_string()));
+    }
+    session.flush().await.map_err(crate::service::internal_error)?;
+
+    // Their joys may be in anyone's feed.
+    if let Err(e) = state.sse.publish(Patch::JoyCards { user_id: None }) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(Redirect::to("/"))
+}
@@ -0,0 +1,285 @@
+use axum::extract::{ConnectInfo, FromRequestParts, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tower_sessions::Session;
+use uuid::Uuid;
+
+use crate::component::flash::Flash;
+use crate::service::auth::{self, RequireAccount};
+use crate::service::state::AppState;
+use crate::service::user::session_user_id;
+
+/// A token bucket: up to `capacity` requests, refilled evenly over `per`.
+#[derive(Clone, Copy, Debug)]
+pub struct Limit {
+    pub capacity: u32,
+    pub per: Duration,
+}
+
+impl Limit {
+    pub const fn new(capacity: u32, per: Duration) -> Self {
+        Self { capacity, per }
+    }
+
+    /// Reads a `capacity/seconds` override such as `5/60` from `var`.
+    pub fn from_env(var: &str, default: Limit) -> Self {
+        let Ok(value) = std::env::var(var) else {
+            return default;
+        };
+        let parsed = value.split_once('/').and_then(|(capacity, seconds)| {
+            Some(Limit::new(capacity.trim().parse().ok()?, Duration::from_secs(seconds.trim().parse().ok()?)))
+        });
+        match parsed {
+            Some(limit) if limit.capacity > 0 && !limit.per.is_zero() => limit,
+            _ => {
+                tracing::warn!(var, value, "ignoring invalid rate limit, expected capacity/seconds");
+                default
+            }
+        }
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.capacity as f64 / self.per.as_secs_f64()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    User(Uuid),
+    Ip(IpAddr),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Route {
+    method: Method,
+    path: &'static str,
+    limit: Limit,
+}
+
+pub struct RateLimiter {
+    routes: Vec<Route>,
+    buckets: Mutex<HashMap<(&'static str, Key), Bucket>>,
+    trust_proxy: bool,
+}
+
+impl RateLimiter {
+    /// With `trust_proxy`, the client IP is taken from `X-Forwarded-For` /
+    /// `X-Real-IP` instead of the socket peer address.
+    pub fn new(trust_proxy: bool) -> Self {
+        Self {
+            routes: Vec::new(),
+            buckets: Mutex::new(HashMap::new()),
+            trust_proxy,
+        }
+    }
+
+    pub fn route(mut self, method: Method, path: &'static str, limit: Limit) -> Self {
+        self.routes.push(Route { method, path, limit });
+        self
+    }
+
+    fn limit_for(&self, method: &Method, path: &str) -> Option<(&'static str, Limit)> {
+        let path = match path.trim_end_matches('/') {
+            "" => "/",
+            p => p,
+        };
+        self.routes
+            .iter()
+            .find(|r| r.method == method && r.path == path)
+            .map(|r| (r.path, r.limit))
+    }
+
+    /// Takes a token from the bucket of every key on `route`, or none at
+    /// all if any bucket is empty, in which case it returns that key and
+    /// how long until it has a token again.
+    fn take(&self, route: &'static str, keys: &[Key], limit: Limit) -> Result<(), (Key, Duration)> {
+        self.take_at(route, keys, limit, Instant::now())
+    }
+
+    fn take_at(&self, route: &'static str, keys: &[Key], limit: Limit, now: Instant) -> Result<(), (Key, Duration)> {
+        let rate = limit.refill_per_sec();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        for key in keys {
+            let bucket = buckets.entry((route, *key)).or_insert(Bucket {
+                tokens: limit.capacity as f64,
+                updated: now,
+            });
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rate).min(limit.capacity as f64);
+            bucket.updated = now;
+
+            if bucket.tokens < 1.0 {
+                return Err((*key, Duration::from_secs_f64((1.0 - bucket.tokens) / rate)));
+            }
+        }
+
+        for key in keys {
+            if let Some(bucket) = buckets.get_mut(&(route, *key)) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops buckets that have refilled completely; they are
+    /// indistinguishable from fresh ones.
+    pub fn purge(&self) {
+        let now = Instant::now();
+        let routes: HashMap<&'static str, Limit> = self.routes.iter().map(|r| (r.path, r.limit)).collect();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets.retain(|(route, _), bucket| {
+            let Some(limit) = routes.get(route) else {
+                return false;
+            };
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens + elapsed * limit.refill_per_sec() < limit.capacity as f64
+        });
+    }
+
+    fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        if self.trust_proxy {
+            let forwarded = headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+                .and_then(|v| v.trim().parse().ok());
+            if forwarded.is_some() {
+                return forwarded;
+            }
+        }
+        peer
+    }
+}
+
+/// Applies the configured per-route token buckets, keyed by both the
+/// client IP and the user, whether from the session or an API token. Must
+/// run inside the session layer.
+pub async fn middleware(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let limiter = &state.rate_limiter;
+    let Some((route, limit)) = limiter.limit_for(req.method(), req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let mut keys = Vec::with_capacity(2);
+    if let Some(ip) = limiter.client_ip(req.headers(), peer) {
+        keys.push(Key::Ip(ip));
+    }
+    let (mut parts, body) = req.into_parts();
+    if auth::bearer(&parts).is_some() {
+        // Resolving the token caches the user for the handler; a bad token
+        // is left for the handler to reject.
+        if let Ok(RequireAccount(user)) = RequireAccount::from_request_parts(&mut parts, &state).await {
+            keys.push(Key::User(user.id));
+        }
+    } else if let Some(session) = parts.extensions.get::<Session>() {
+        match session_user_id(session).await {
+            Ok(Some(id)) => keys.push(Key::User(id)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "rate limiter could not read session"),
+        }
+    }
+    let req = Request::from_parts(parts, body);
+
+    if let Err((key, retry_after)) = limiter.take(route, &keys, limit) {
+        tracing::info!(route, ?key, "rate limited");
+        let seconds = retry_after.as_secs().max(1);
+        let mut res = Flash::error(format!("Slow down! Please try again in {} seconds.", seconds))
+            .into_response_with(StatusCode::TOO_MANY_REQUESTS);
+        res.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        return res;
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTE: &str = "/joy-form";
+    const LIMIT: Limit = Limit::new(2, Duration::from_secs(2));
+
+    fn ip(value: &str) -> Key {
+        Key::Ip(value.parse().unwrap())
+    }
+
+    #[test]
+    fn bucket_empties_and_refills() {
+        let limiter = RateLimiter::new(false);
+        let start = Instant::now();
+        let keys = [ip("10.0.0.1")];
+
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, start).is_ok());
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, start).is_ok());
+        let (_, retry_after) = limiter.take_at(ROUTE, &keys, LIMIT, start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(1));
+
+        // One token a second comes back, but never more than the capacity.
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, start + Duration::from_secs(1)).is_ok());
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, start + Duration::from_secs(1)).is_err());
+        let later = start + Duration::from_secs(60);
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, later).is_ok());
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, later).is_ok());
+        assert!(limiter.take_at(ROUTE, &keys, LIMIT, later).is_err());
+    }
+
+    #[test]
+    fn rejection_spends_no_tokens() {
+        let limiter = RateLimiter::new(false);
+        let now = Instant::now();
+        let shared = ip("10.0.0.1");
+        let user = Key::User(Uuid::new_v4());
+
+        assert!(limiter.take_at(ROUTE, &[user], LIMIT, now).is_ok());
+        assert!(limiter.take_at(ROUTE, &[user], LIMIT, now).is_ok());
+
+        // The user's bucket is empty, so the shared IP bucket must stay full.
+        let (key, _) = limiter.take_at(ROUTE, &[shared, user], LIMIT, now).unwrap_err();
+        assert_eq!(key, user);
+        assert!(limiter.take_at(ROUTE, &[shared], LIMIT, now).is_ok());
+        assert!(limiter.take_at(ROUTE, &[shared], LIMIT, now).is_ok());
+        assert!(limiter.take_at(ROUTE, &[shared], LIMIT, now).is_err());
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarded_headers_unless_trusted() {
+        let peer: IpAddr = "192.0.2.1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.1"));
+
+        assert_eq!(RateLimiter::new(false).client_ip(&headers, Some(peer)), Some(peer));
+        assert_eq!(
+            RateLimiter::new(true).client_ip(&headers, Some(peer)),
+            Some("203.0.113.7".parse().unwrap()),
+        );
+    }
+
+    #[test]
+    fn client_ip_falls_back_from_forwarded_headers() {
+        let peer: IpAddr = "192.0.2.1".parse().unwrap();
+        let limiter = RateLimiter::new(true);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.9"));
+        assert_eq!(limiter.client_ip(&headers, Some(peer)), Some("203.0.113.9".parse().unwrap()));
+
+        headers.insert("x-forwarded-for", HeaderValue::from_static("not an ip"));
+        assert_eq!(limiter.client_ip(&headers, Some(peer)), Some(peer));
+        assert_eq!(limiter.client_ip(&HeaderMap::new(), None), None);
+    }
+}
@@ -0,0 +1,236 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+use sqlx::{PgPool, Row};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::service::auth::RequireAccount;
+use crate::service::moderation::Status;
+use crate::service::sse::Patch;
+use crate::service::state::AppState;
+
+/// Number of reports after which a joy is held for review, unless overridden
+/// by `REPORT_THRESHOLD`.
+pub const DEFAULT_REPORT_THRESHOLD: i64 = 3;
+
+#[derive(Deserialize, Default)]
+pub struct NewReport {
+    pub reason: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct ReportService {
+    db: PgPool,
+    threshold: i64,
+}
+
+impl ReportService {
+    pub fn new(db: PgPool, threshold: i64) -> Self {
+        Self { db, threshold }
+    }
+
+    /// Records a report and hides the joy from the reporter. Once the joy
+    /// reaches the report threshold since its last review it is held for
+    /// review. Returns whether
+    /// this report caused the joy to be held.
+    pub async fn report(&self, joy_id: &Uuid, user_id: &Uuid, reason: Option<String>) -> Result<bool, String> {
+        let reason = reason
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty());
+
+        sqlx::query(r#"
+            INSERT INTO reports (joy_id, user_id, reason)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (joy_id, user_id) DO UPDATE SET reason = COALESCE(EXCLUDED.reason, reports.reason)
+        "#)
+            .bind(joy_id)
+            .bind(user_id)
+            .bind(reason)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.hide_joy(joy_id, user_id).await?;
+
+        let row = sqlx::query(r#"
+            SELECT j.status, j.reviewed, ARRAY(SELECT r.created FROM reports r WHERE r.joy_id = j.id) AS reports
+            FROM joys j
+            WHERE j.id = $1
+        "#)
+            .bind(joy_id)
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let Some(row) = row else { return Ok(false) };
+        if Status::parse(&row.get::<String, _>("status")) != Some(Status::Approved) {
+            return Ok(false);
+        }
+
+        let reviewed: Option<OffsetDateTime> = row.get("reviewed");
+        let count = reports_since_review(&row.get::<Vec<OffsetDateTime>, _>("reports"), reviewed);
+        if count < self.threshold {
+            return Ok(false);
+        }
+
+        // Only hold the joy if nobody has reviewed it in the meantime.
+        let held = sqlx::query(r#"
+            UPDATE joys SET
+                status = 'held',
+                moderation_reason = 'reported ' || $2 || ' times'
+            WHERE id = $1
+                AND status = 'approved'
+                AND reviewed IS NOT DISTINCT FROM $3
+        "#)
+            .bind(joy_id)
+            .bind(count)
+            .bind(reviewed)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(held.rows_affected() > 0)
+    }
+
+    pub async fn hide_joy(&self, joy_id: &Uuid, user_id: &Uuid) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO user_hides (user_id, joy_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, joy_id) DO NOTHING
+        "#)
+            .bind(user_id)
+            .bind(joy_id)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    pub async fn hide_author(&self, author_id: &Uuid, user_id: &Uuid) -> Result<(), String> {
+        sqlx::query(r#"
+            INSERT INTO user_hides (user_id, author_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, author_id) DO NOTHING
+        "#)
+            .bind(user_id)
+            .bind(author_id)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Counts the reports made since a moderator last reviewed the joy, so an
+/// approved joy is not held again by the reports it was approved despite.
+fn reports_since_review(reports: &[OffsetDateTime], reviewed: Option<OffsetDateTime>) -> i64 {
+    reports.iter()
+        .filter(|created| reviewed.is_none_or(|reviewed| **created > reviewed))
+        .count() as i64
+}
+
+/// Loads a joy visible to the current user that they did not write.
+async fn others_joy(state: &AppState, id: Uuid, user_id: Uuid) -> Result<crate::service::joy::Joy, (StatusCode, String)> {
+    let joy = state.joys.get_for_user(id, user_id).await
+        .map_err(crate::service::internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "joy not found".to_string()))?;
+
+    if joy.user_id == Some(user_id) {
+        return Err((StatusCode::BAD_REQUEST, "that is your own joy".to_string()));
+    }
+
+    Ok(joy)
+}
+
+pub async fn report_joy(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Path(id): Path<Uuid>,
+    form: Option<Json<NewReport>>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    others_joy(&state, id, user.id).await?;
+
+    let Json(form) = form.unwrap_or_default();
+    let held = state.reports.report(&id, &user.id, form.reason).await
+        .map_err(crate::service::internal_error)?;
+
+    let patch = if held {
+        tracing::info!(joy_id = %id, "joy held after reaching report threshold");
+        Patch::JoyCard(id)
+    } else {
+        Patch::JoyCards { user_id: Some(user.id) }
+    };
+    if let Err(e) = state.sse.publish(patch) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn hide_joy(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    others_joy(&state, id, user.id).await?;
+
+    state.reports.hide_joy(&id, &user.id).await
+        .map_err(crate::service::internal_error)?;
+
+    if let Err(e) = state.sse.publish(Patch::JoyCards { user_id: Some(user.id) }) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn hide_author(
+    State(state): State<AppState>,
+    RequireAccount(user): RequireAccount,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let joy = others_joy(&state, id, user.id).await?;
+
+    // Anonymised joys have no author left to hide, so hide the joy itself.
+    match joy.user_id {
+        Some(author_id) => state.reports.hide_author(&author_id, &user.id).await,
+        None => state.reports.hide_joy(&joy.id, &user.id).await,
+    }
+        .map_err(crate::service::internal_error)?;
+
+    if let Err(e) = state.sse.publish(Patch::JoyCards { user_id: Some(user.id) }) {
+        tracing::warn!(?e, "failed to publish SSE patch");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    #[test]
+    fn unreviewed_joys_count_every_report() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let reports = [now, now + Duration::minutes(1), now + Duration::minutes(2)];
+        assert_eq!(reports_since_review(&reports, None), 3);
+    }
+
+    #[test]
+    fn approved_joys_stay_approved_after_one_more_report() {
+        let now = OffsetDateTime::UNIX_EPOCH;
+        let mut reports = vec![now, now + Duration::minutes(1), now + Duration::minutes(2)];
+        let reviewed = now + Duration::hours(1);
+        assert_eq!(reports_since_review(&reports, Some(reviewed)), 0);
+
+        reports.push(reviewed + Duration::minutes(5));
+        let count = reports_since_review(&reports, Some(reviewed));
+        assert_eq!(count, 1);
+        assert!(count < DEFAULT_REPORT_THRESHOLD);
+    }
+}
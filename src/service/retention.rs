@@ -0,0 +1,244 @@
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// What happens to joys once they are older than the retention period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoyExpiry {
+    /// Delete the joy along with its reactions and reports.
+    Delete,
+    /// Keep the joy's text but drop its author, frustration, context and
+    /// accuracy. Joys their author already deleted are removed outright.
+    Anonymise,
+}
+
+impl JoyExpiry {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "delete" => Some(JoyExpiry::Delete),
+            "anonymise" | "anonymize" => Some(JoyExpiry::Anonymise),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JoyExpiry::Delete => "delete",
+            JoyExpiry::Anonymise => "anonymise",
+        }
+    }
+}
+
+/// Which old data the retention job removes. Everything is kept forever
+/// unless configured otherwise.
+#[derive(Clone, Copy, Debug)]
+pub struct RetentionPolicy {
+    /// Joys older than this are deleted or anonymised.
+    pub joys: Option<(Duration, JoyExpiry)>,
+    /// Joys older than this lose their location.
+    pub points: Option<Duration>,
+    pub interval: Duration,
+    /// Only log what a run would do.
+    pub dry_run: bool,
+}
+
+impl RetentionPolicy {
+    /// Reads `RETENTION_JOY_DAYS`, `RETENTION_JOY_ACTION` (`delete` or
+    /// `anonymise`, the default), `RETENTION_POINT_DAYS`,
+    /// `RETENTION_INTERVAL_MINUTES` (default 60) and `RETENTION_DRY_RUN`.
+    pub fn from_env() -> Result<Self, String> {
+        let days = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60))
+        };
+
+        let action = match std::env::var("RETENTION_JOY_ACTION") {
+            Ok(value) => JoyExpiry::parse(&value)
+                .ok_or_else(|| format!("RETENTION_JOY_ACTION must be delete or anonymise, not {:?}", value))?,
+            Err(_) => JoyExpiry::Anonymise,
+        };
+
+        Ok(Self {
+            joys: days("RETENTION_JOY_DAYS").map(|age| (age, action)),
+            points: days("RETENTION_POINT_DAYS"),
+            interval: std::env::var("RETENTION_INTERVAL_MINUTES")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .map(|minutes| Duration::from_secs(minutes * 60))
+                .unwrap_or(Duration::from_secs(60 * 60)),
+            dry_run: std::env::var("RETENTION_DRY_RUN").is_ok_and(|v| v == "true"),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.joys.is_some() || self.points.is_some()
+    }
+
+    pub fn joy_days(&self) -> Option<u64> {
+        self.joys.map(|(age, _)| age.as_secs() / (24 * 60 * 60))
+    }
+
+    pub fn joy_action(&self) -> Option<&'static str> {
+        self.joys.map(|(_, action)| action.as_str())
+    }
+
+    pub fn point_days(&self) -> Option<u64> {
+        self.points.map(|age| age.as_secs() / (24 * 60 * 60))
+    }
+}
+
+/// How many joys a run affected, or would affect.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetentionReport {
+    pub deleted: u64,
+    pub anonymised: u64,
+    /// Joys whose location was removed, not counting deleted ones.
+    pub points: u64,
+}
+
+pub struct RetentionService {
+    db: PgPool,
+    policy: RetentionPolicy,
+}
+
+impl RetentionService {
+    pub fn new(db: PgPool, policy: RetentionPolicy) -> Self {
+        Self { db, policy }
+    }
+
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    /// What a run would do right now, without changing anything.
+    pub async fn report(&self) -> Result<RetentionReport, String> {
+        let (joy_age, action) = self.joy_cutoff();
+        let row = sqlx::query(r#"
+            WITH expired AS (
+                SELECT
+                    j.user_id,
+                    j.deleted,
+                    j.point,
+                    COALESCE(j.created < NOW() - make_interval(secs => $1), FALSE) AS joy,
+                    COALESCE(j.created < NOW() - make_interval(secs => $3), FALSE) AS point_expired
+                FROM joys j
+            )
+            SELECT
+                COUNT(*) FILTER (WHERE joy AND ($2 OR deleted IS NOT NULL)) AS deleted,
+                COUNT(*) FILTER (WHERE joy AND NOT $2 AND deleted IS NULL AND user_id IS NOT NULL) AS anonymised,
+                COUNT(*) FILTER (
+                    WHERE point_expired AND point IS NOT NULL
+                        AND NOT (joy AND ($2 OR deleted IS NOT NULL))
+                ) AS points
+            FROM expired
+        "#)
+            .bind(joy_age)
+            .bind(action == JoyExpiry::Delete)
+            .bind(self.point_cutoff())
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(RetentionReport {
+            deleted: row.get::<i64, _>("deleted") as u64,
+            anonymised: row.get::<i64, _>("anonymised") as u64,
+            points: row.get::<i64, _>("points") as u64,
+        })
+    }
+
+    /// Applies the policy in a single transaction.
+    pub async fn run(&self) -> Result<RetentionReport, String> {
+        let (joy_age, action) = self.joy_cutoff();
+        let mut tx = self.db.begin().await.map_err(|e| e.to_string())?;
+
+        let deleted = sqlx::query(r#"
+            DELETE FROM joys
+            WHERE created < NOW() - make_interval(secs => $1)
+                AND ($2 OR deleted IS NOT NULL)
+        "#)
+            .bind(joy_age)
+            .bind(action == JoyExpiry::Delete)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+
+        let anonymised = sqlx::query(r#"
+            UPDATE joys SET user_id = NULL, frustration = '', context = '', accuracy = NULL
+            WHERE created < NOW() - make_interval(secs => $1)
+                AND user_id IS NOT NULL
+        "#)
+            .bind(joy_age)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+
+        let points = sqlx::query(r#"
+            UPDATE joys SET point = NULL, accuracy = NULL
+            WHERE created < NOW() - make_interval(secs => $1)
+                AND point IS NOT NULL
+        "#)
+            .bind(self.point_cutoff())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(RetentionReport { deleted, anonymised, points })
+    }
+
+    /// The joy age in seconds and what happens past it. Without a joy
+    /// policy the age is NULL, which no `created` comparison satisfies.
+    fn joy_cutoff(&self) -> (Option<f64>, JoyExpiry) {
+        match self.policy.joys {
+            Some((age, action)) => (Some(age.as_secs_f64()), action),
+            None => (None, JoyExpiry::Anonymise),
+        }
+    }
+
+    fn point_cutoff(&self) -> Option<f64> {
+        self.policy.points.map(|age| age.as_secs_f64())
+    }
+}
+
+/// Runs the retention policy every `interval`, or in dry-run mode only logs
+/// what it would do. Does nothing if no policy is configured.
+pub fn spawn_retention(retention: Arc<RetentionService>) {
+    let policy = *retention.policy();
+    if !policy.is_enabled() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(policy.interval);
+        loop {
+            interval.tick().await;
+
+            let started = Instant::now();
+            let result = if policy.dry_run {
+                retention.report().await
+            } else {
+                retention.run().await
+            };
+
+            match result {
+                Ok(report) => tracing::info!(
+                    dry_run = policy.dry_run,
+                    deleted = report.deleted,
+                    anonymised = report.anonymised,
+                    points = report.points,
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "{}",
+                    if policy.dry_run { "Retention dry run" } else { "Retention run" },
+                ),
+                Err(e) => tracing::error!("Retention run failed: {}", e),
+            }
+        }
+    });
+}
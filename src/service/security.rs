@@ -0,0 +1,84 @@
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use uuid::Uuid;
+
+use crate::service::state::AppState;
+
+/// Two years, as HSTS preload lists expect.
+const HSTS: &str = "max-age=63072000; includeSubDomains";
+
+/// Everything but geolocation, which only our own pages may ask for.
+const PERMISSIONS_POLICY: &str =
+    "geolocation=(self), camera=(), microphone=(), payment=(), usb=()";
+
+/// The nonce this response's Content-Security-Policy allows scripts and
+/// styles with. Templates put it on the tags they load.
+#[derive(Clone, Debug, Default)]
+pub struct CspNonce(pub String);
+
+#[async_trait::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CspNonce {
+    type Rejection = std::convert::Infallible;
+
+    /// Empty when the security middleware is not installed, in which case
+    /// there is no policy for it to satisfy either.
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<CspNonce>().cloned().unwrap_or_default())
+    }
+}
+
+pub struct SecurityHeaders {
+    /// Whether the app is served over HTTPS, directly or behind a proxy.
+    hsts: bool,
+}
+
+impl SecurityHeaders {
+    pub fn new(hsts: bool) -> Self {
+        Self { hsts }
+    }
+
+    /// HSTS is sent with built-in TLS, or behind a TLS-terminating proxy
+    /// when `HSTS=true`: only once every request arrives over HTTPS.
+    pub fn from_env(tls: bool) -> Self {
+        Self::new(tls || std::env::var("HSTS").is_ok_and(|v| v == "true"))
+    }
+
+    /// Datastar evaluates its expressions with `Function`, hence
+    /// `'unsafe-eval'`; inline scripts and styles need the nonce.
+    fn policy(nonce: &str) -> String {
+        format!(
+            "default-src 'self'; \
+             script-src 'self' 'nonce-{nonce}' 'unsafe-eval'; \
+             style-src 'self' 'nonce-{nonce}'; \
+             img-src 'self' data:; \
+             connect-src 'self'; \
+             object-src 'none'; \
+             base-uri 'self'; \
+             form-action 'self'; \
+             frame-ancestors 'none'"
+        )
+    }
+}
+
+/// Sets the security headers on every response and hands the request a
+/// fresh CSP nonce.
+pub async fn middleware(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    let nonce = Uuid::new_v4().simple().to_string();
+    req.extensions_mut().insert(CspNonce(nonce.clone()));
+
+    let mut res = next.run(req).await;
+    let headers = res.headers_mut();
+    if let Ok(policy) = HeaderValue::from_str(&SecurityHeaders::policy(&nonce)) {
+        headers.insert(header::CONTENT_SECURITY_POLICY, policy);
+    }
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(header::REFERRER_POLICY, HeaderValue::from_static("strict-origin-when-cross-origin"));
+    headers.insert(HeaderName::from_static("permissions-policy"), HeaderValue::from_static(PERMISSIONS_POLICY));
+    if state.security.hsts {
+        headers.insert(header::STRICT_TRANSPORT_SECURITY, HeaderValue::from_static(HSTS));
+    }
+    res
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tower_sessions::session::Expiry;
+use tower_sessions::session_store::ExpiredDeletion;
+use tower_sessions_sqlx_store::PostgresStore;
+use crate::service::user::{UserService, LAST_SEEN_INTERVAL};
+
+/// How long sessions live and how often expired ones are cleaned up.
+#[derive(Clone, Copy, Debug)]
+pub struct SessionPolicy {
+    /// Sessions expire after this long without a request.
+    pub inactivity: Duration,
+    /// How often expired sessions and abandoned users are deleted.
+    pub cleanup_interval: Duration,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self {
+            inactivity: Duration::from_secs(30 * 24 * 60 * 60),
+            cleanup_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl SessionPolicy {
+    /// Reads `SESSION_INACTIVITY_DAYS` and `SESSION_CLEANUP_MINUTES`, falling
+    /// back to the defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0);
+        Self {
+            inactivity: var("SESSION_INACTIVITY_DAYS")
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60))
+                .unwrap_or(default.inactivity),
+            cleanup_interval: var("SESSION_CLEANUP_MINUTES")
+                .map(|minutes| Duration::from_secs(minutes * 60))
+                .unwrap_or(default.cleanup_interval),
+        }
+    }
+
+    pub fn expiry(&self) -> Expiry {
+        Expiry::OnInactivity(time::Duration::seconds(self.inactivity.as_secs() as i64))
+    }
+}
+
+/// Periodically deletes expired sessions, then the anonymous users left
+/// behind by them. Users are only collected once they have been idle for
+/// longer than a session can live, plus `LAST_SEEN_INTERVAL` because
+/// `last_seen` may lag that far behind, so a live session never loses its
+/// user.
+pub fn spawn_cleanup(store: PostgresStore, users: Arc<UserService>, policy: SessionPolicy) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(policy.cleanup_interval);
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = store.delete_expired().await {
+                tracing::error!("Failed to delete expired sessions: {}", e);
+                continue;
+            }
+
+            match users.delete_abandoned(policy.inactivity + LAST_SEEN_INTERVAL).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Deleted {} abandoned anonymous users", count),
+                Err(e) => tracing::error!("Failed to delete abandoned users: {}", e),
+            }
+        }
+    });
+}
@@ -1,16 +1,40 @@
 use {
-    axum::{extract::State, response::{sse::Event, Sse}},
+    axum::{extract::State, http::StatusCode, response::{sse::Event, Sse}},
     core::convert::Infallible,
     futures_util::StreamExt,
     tokio::sync::broadcast,
     tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    uuid::Uuid,
 };
 
+use crate::component::joy_card::{JoyCard, JoyCardProps};
+use crate::component::joy_cards::JoyCards;
+use crate::component::{Component, NoProps};
+use crate::service::auth::CurrentUser;
+use crate::service::locale::Locale;
 use crate::service::state::AppState;
 
+#[derive(Clone, Debug)]
+pub enum Patch {
+    /// The joy card list changed; each subscriber re-renders its own list.
+    /// Restricted to the given user's streams when set.
+    JoyCards { user_id: Option<Uuid> },
+    /// A joy that changed; each subscriber re-renders its card for their own user,
+    /// or removes it if it is no longer visible to them.
+    JoyCard(Uuid),
+    /// Remove the element with the given DOM id.
+    Remove(String),
+    /// A JSON signals patch, merged into the store of the element matched by
+    /// `selector` or into the root store when there is none.
+    Signals {
+        selector: Option<String>,
+        signals: String,
+    },
+}
+
 #[derive(Clone)]
 pub struct SseService {
-    html_tx: broadcast::Sender<String>,
+    html_tx: broadcast::Sender<Patch>,
 }
 
 impl SseService {
@@ -19,34 +43,89 @@ impl SseService {
         Self { html_tx }
     }
 
-    pub fn publish_html(&self, html: String) -> Result<usize, broadcast::error::SendError<String>> {
+    pub fn publish(&self, patch: Patch) -> Result<usize, broadcast::error::SendError<Patch>> {
         if self.html_tx.receiver_count() == 0 {
             return Ok(0);
         }
-        self.html_tx.send(html)
+        self.html_tx.send(patch)
     }
 
-    pub fn subscriber(&self) -> broadcast::Receiver<String> {
+    pub fn subscriber(&self) -> broadcast::Receiver<Patch> {
         self.html_tx.subscribe()
     }
 }
 
+pub fn elements_event(html: &str) -> Event {
+    let cleaned = html.replace(['\n', '\r'], "");
+    Event::default()
+        .event("datastar-patch-elements")
+        .data(format!("elements {}", cleaned))
+}
+
+pub fn remove_event(id: &str) -> Event {
+    Event::default()
+        .event("datastar-patch-elements")
+        .data(format!("mode remove\nselector #{}", id))
+}
+
+fn signals_event(selector: Option<&str>, signals: &str) -> Event {
+    let data = match selector {
+        Some(selector) => format!("selector {}\nsignals {}", selector, signals),
+        None => format!("signals {}", signals),
+    };
+    Event::default()
+        .event("datastar-patch-signals")
+        .data(data)
+}
+
 pub async fn events(
     State(state): State<AppState>,
-) -> Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>> {
+    CurrentUser(user): CurrentUser,
+    locale: Locale,
+) -> Result<Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
     let rx = state.sse.subscriber();
-    let stream = BroadcastStream::new(rx).filter_map(|msg| async move {
-        match msg {
-            Ok(html) => {
-                let cleaned = html.replace(['\n', '\r'], "");
-                let data = format!("elements {}", cleaned);
-                Some(Ok(Event::default()
-                    .event("datastar-patch-elements")
-                    .data(data)
-                ))
-            },
-            Err(BroadcastStreamRecvError::Lagged(_)) => None,
+    let stream = BroadcastStream::new(rx).filter_map(move |msg| {
+        let state = state.clone();
+        let user = user.clone();
+        async move {
+            match msg {
+                Ok(Patch::JoyCards { user_id }) => {
+                    if user_id.is_some_and(|id| id != user.id) {
+                        return None;
+                    }
+                    let patch = JoyCards::build(&state, &user, locale, NoProps::default())
+                        .await
+                        .map_err(|(_, e)| e)
+                        .and_then(|cards| cards.patch());
+                    match patch {
+                        Ok(event) => Some(Ok(event)),
+                        Err(e) => {
+                            tracing::warn!(error = %e, "failed to render joy cards for SSE");
+                            None
+                        }
+                    }
+                }
+                Ok(Patch::Remove(id)) => Some(Ok(remove_event(&id))),
+                Ok(Patch::Signals { selector, signals }) => {
+                    Some(Ok(signals_event(selector.as_deref(), &signals)))
+                }
+                Ok(Patch::JoyCard(id)) => match JoyCard::build(&state, &user, locale, JoyCardProps { id }).await {
+                    Ok(card) => match card.patch() {
+                        Ok(event) => Some(Ok(event)),
+                        Err(e) => {
+                            tracing::warn!(error = %e, "failed to render joy card for SSE");
+                            None
+                        }
+                    },
+                    Err((StatusCode::NOT_FOUND, _)) => Some(Ok(remove_event(&JoyCard::dom_id(&id)))),
+                    Err((_, e)) => {
+                        tracing::warn!(error = %e, "failed to render joy card for SSE");
+                        None
+                    }
+                },
+                Err(BroadcastStreamRecvError::Lagged(_)) => None,
+            }
         }
     });
-    Sse::new(stream)
+    Ok(Sse::new(stream))
 }
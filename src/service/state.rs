@@ -1,10 +1,36 @@
 use std::sync::Arc;
 
-use super::{joy::JoyService, sse::SseService, user::UserService};
+use super::{
+    admin::AdminService,
+    debounce::Debouncer,
+    draft::DraftService,
+    gazetteer::Gazetteer,
+    joy::JoyService,
+    preferences::PreferencesService,
+    privacy::PrivacyService,
+    rate_limit::RateLimiter,
+    report::ReportService,
+    retention::RetentionService,
+    security::SecurityHeaders,
+    sse::SseService,
+    token::TokenService,
+    user::UserService,
+};
 
 #[derive(Clone)]
 pub struct AppState {
+    pub admin: Arc<AdminService>,
     pub users: Arc<UserService>,
+    pub drafts: Arc<DraftService>,
     pub joys: Arc<JoyService>,
+    pub places: Arc<Gazetteer>,
+    pub preferences: Arc<PreferencesService>,
+    pub privacy: Arc<PrivacyService>,
+    pub reports: Arc<ReportService>,
+    pub retention: Arc<RetentionService>,
     pub sse: Arc<SseService>,
+    pub tokens: Arc<TokenService>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub location_debounce: Arc<Debouncer>,
+    pub security: Arc<SecurityHeaders>,
 }
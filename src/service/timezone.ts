@@ -0,0 +1,34 @@
+export class TimeZoneService {
+    readonly ENDPOINT = '/user/timezone';
+
+    /**
+     * Reports the browser's offset from UTC when it differs from the one the
+     * page was rendered with, so later server-rendered times match the clock.
+     */
+    async sync(): Promise<void> {
+        const app = document.querySelector<HTMLElement>('app-app');
+        const offset = -new Date().getTimezoneOffset();
+        if (!app || app.dataset.utcOffset === String(offset)) {
+            return;
+        }
+
+        try {
+            const response = await fetch(this.ENDPOINT, {
+                method: 'POST',
+                headers: {
+                    'Content-Type': 'application/json',
+                    'X-CSRF-Token': document.querySelector<HTMLMetaElement>('meta[name="csrf-token"]')?.content ?? '',
+                },
+                credentials: 'include',
+                body: JSON.stringify({ offset }),
+            });
+            if (response.ok) {
+                app.dataset.utcOffset = String(offset);
+            } else {
+                console.error(`Failed to store time zone: ${response.status}`);
+            }
+        } catch (error) {
+            console.error('Failed to store time zone:', error);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use axum::extract::Host;
+use axum::http::Uri;
+use axum::response::Redirect;
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Certificates are often replaced as a cert and a key write in quick
+/// succession; reloading after a pause sees both.
+const RELOAD_DELAY: Duration = Duration::from_secs(1);
+
+/// Built-in TLS for deployments without a reverse proxy in front.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// Where to listen for plain HTTP and redirect it to HTTPS, if at all.
+    pub redirect_from: Option<SocketAddr>,
+}
+
+impl TlsConfig {
+    /// Reads `TLS_CERT` and `TLS_KEY` (PEM files) and the optional
+    /// `TLS_REDIRECT_FROM` address. TLS is off unless both files are given.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let (cert, key) = match (var("TLS_CERT"), var("TLS_KEY")) {
+            (Some(cert), Some(key)) => (PathBuf::from(cert), PathBuf::from(key)),
+            (None, None) => return Ok(None),
+            _ => return Err("TLS_CERT and TLS_KEY must be set together".to_string()),
+        };
+        let redirect_from = var("TLS_REDIRECT_FROM")
+            .map(|addr| addr.parse().map_err(|e| format!("invalid TLS_REDIRECT_FROM {}: {}", addr, e)))
+            .transpose()?;
+        Ok(Some(Self { cert, key, redirect_from }))
+    }
+
+    /// Loads the certificate chain and key. The server offers HTTP/2 and
+    /// HTTP/1.1 over ALPN, so browsers multiplex SSE over one connection.
+    pub async fn load(&self) -> Result<RustlsConfig, String> {
+        // Only the ring provider is compiled in; make it the process default.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        RustlsConfig::from_pem_file(&self.cert, &self.key)
+            .await
+            .map_err(|e| format!("failed to load TLS certificate {}: {}", self.cert.display(), e))
+    }
+
+    /// Reloads `config` whenever the certificate or key changes on disk, so
+    /// renewed certificates are served without a restart. The directories
+    /// are watched rather than the files, which renewal tools tend to
+    /// replace. Watching stops when the returned watcher is dropped.
+    pub fn watch(&self, config: RustlsConfig) -> Result<RecommendedWatcher, String> {
+        let runtime = tokio::runtime::Handle::current();
+        let (cert, key) = (self.cert.clone(), self.key.clone());
+        let is_ours = {
+            let (cert, key) = (cert.clone(), key.clone());
+            move |path: &Path| path.file_name() == cert.file_name() || path.file_name() == key.file_name()
+        };
+
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => return tracing::warn!(error = %e, "certificate watcher failed"),
+            };
+            if event.kind.is_access() || !event.paths.iter().any(|path| is_ours(path)) {
+                return;
+            }
+
+            let (config, cert, key) = (config.clone(), cert.clone(), key.clone());
+            runtime.spawn(async move {
+                tokio::time::sleep(RELOAD_DELAY).await;
+                match config.reload_from_pem_file(&cert, &key).await {
+                    Ok(()) => tracing::info!(cert = %cert.display(), "reloaded TLS certificate"),
+                    // The old certificate stays in use; the next change retries.
+                    Err(e) => tracing::warn!(error = %e, "failed to reload TLS certificate"),
+                }
+            });
+        })
+        .map_err(|e| e.to_string())?;
+
+        let mut dirs = vec![parent(&self.cert), parent(&self.key)];
+        dirs.dedup();
+        for dir in dirs {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("failed to watch {}: {}", dir.display(), e))?;
+        }
+        Ok(watcher)
+    }
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."))
+}
+
+/// Serves plain HTTP on `from`, permanently redirecting every request to
+/// the same host and path on the HTTPS port.
+pub async fn redirect_to_https(from: SocketAddr, https_port: u16) -> std::io::Result<()> {
+    let app = Router::new().fallback(move |Host(host): Host, uri: Uri| async move {
+        let host = match host.rsplit_once(':') {
+            Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+            _ => host.as_str(),
+        };
+        let authority = match https_port {
+            443 => host.to_string(),
+            port => format!("{}:{}", host, port),
+        };
+        let path = uri.path_and_query().map_or("/", |p| p.as_str());
+        Redirect::permanent(&format!("https://{}{}", authority, path))
+    });
+
+    let listener = tokio::net::TcpListener::bind(from).await?;
+    tracing::debug!("redirecting HTTP on {} to HTTPS", from);
+    axum::serve(listener, app).await
+}
@@ -0,0 +1,182 @@
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// Every secret starts with this, so leaked tokens are easy to grep for.
+const TOKEN_PREFIX: &str = "joyus_";
+
+/// Characters of the secret kept in the clear to identify a token.
+const VISIBLE_CHARS: usize = 10;
+
+/// What a personal access token may do. Reading covers safe methods
+/// (`GET`, `HEAD`), writing everything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    Read,
+    Write,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 2] = [Scope::Read, Scope::Write];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == value)
+    }
+
+    /// Catalog id of the scope's description.
+    pub fn label_id(&self) -> &'static str {
+        match self {
+            Scope::Read => "scope-read",
+            Scope::Write => "scope-write",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    pub created: OffsetDateTime,
+    pub expires: Option<OffsetDateTime>,
+    pub last_used: Option<OffsetDateTime>,
+}
+
+impl ApiToken {
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= OffsetDateTime::now_utc())
+    }
+
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get::<Uuid, _>("id"),
+            user_id: row.get::<Uuid, _>("user_id"),
+            name: row.get::<String, _>("name"),
+            prefix: row.get::<String, _>("prefix"),
+            scopes: row
+                .get::<Vec<String>, _>("scopes")
+                .iter()
+                .filter_map(|s| Scope::parse(s))
+                .collect(),
+            created: row.get::<OffsetDateTime, _>("created"),
+            expires: row.get::<Option<OffsetDateTime>, _>("expires"),
+            last_used: row.get::<Option<OffsetDateTime>, _>("last_used"),
+        }
+    }
+}
+
+fn hash(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// A new secret with 244 random bits, from two v4 UUIDs.
+fn generate() -> String {
+    format!("{}{}{}", TOKEN_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+pub struct TokenService {
+    db: PgPool,
+}
+
+impl TokenService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Issues a token for `user_id`. Returns the stored token and its
+    /// secret, which cannot be recovered later.
+    pub async fn create(
+        &self,
+        user_id: &Uuid,
+        name: &str,
+        scopes: &[Scope],
+        lifetime: Option<Duration>,
+    ) -> Result<(ApiToken, String), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("name cannot be empty".to_string());
+        }
+        if scopes.is_empty() {
+            return Err("pick at least one scope".to_string());
+        }
+
+        let secret = generate();
+        let scopes: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+        let row = sqlx::query(r#"
+            INSERT INTO api_tokens (user_id, name, token_hash, prefix, scopes, expires)
+            VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(secs => $6))
+            RETURNING id, user_id, name, prefix, scopes, created, expires, last_used
+        "#)
+            .bind(user_id)
+            .bind(name)
+            .bind(hash(&secret))
+            .bind(&secret[..TOKEN_PREFIX.len() + VISIBLE_CHARS])
+            .bind(&scopes)
+            .bind(lifetime.map(|l| l.as_seconds_f64()))
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((ApiToken::from_row(&row), secret))
+    }
+
+    pub async fn list(&self, user_id: &Uuid) -> Result<Vec<ApiToken>, String> {
+        let rows = sqlx::query(r#"
+            SELECT id, user_id, name, prefix, scopes, created, expires, last_used
+            FROM api_tokens
+            WHERE user_id = $1
+            ORDER BY created DESC
+        "#)
+            .bind(user_id)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(rows.iter().map(ApiToken::from_row).collect())
+    }
+
+    /// Deletes one of `user_id`'s tokens. Returns whether it existed.
+    pub async fn revoke(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, String> {
+        let result = sqlx::query(r#"DELETE FROM api_tokens WHERE id = $1 AND user_id = $2"#)
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Looks up an unexpired token by its secret and records that it was used.
+    pub async fn authenticate(&self, secret: &str) -> Result<Option<ApiToken>, String> {
+        if !secret.starts_with(TOKEN_PREFIX) {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(r#"
+            UPDATE api_tokens SET last_used = NOW()
+            WHERE token_hash = $1 AND (expires IS NULL OR expires > NOW())
+            RETURNING id, user_id, name, prefix, scopes, created, expires, last_used
+        "#)
+            .bind(hash(secret))
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(row.as_ref().map(ApiToken::from_row))
+    }
+}
@@ -1,19 +1,72 @@
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::Json;
-use axum::response::Html;
 use serde::Deserialize;
 use sqlx::{PgPool, Row};
+use std::time::Duration;
+use time::OffsetDateTime;
 use tower_sessions::Session;
 use uuid::Uuid;
+use crate::service::auth::CurrentUser;
+use crate::service::sse::Patch;
 use crate::service::state::AppState;
 
 const APP_USER_ID_KEY: &str = "app_user_id";
+const CSRF_TOKEN_KEY: &str = "csrf_token";
+const LAST_SEEN_KEY: &str = "last_seen";
+
+/// How stale `users.last_seen` may get before a request refreshes it.
+pub const LAST_SEEN_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Reads the application user ID from the session without creating a user.
+pub async fn session_user_id(session: &Session) -> Result<Option<Uuid>, String> {
+    session.get(APP_USER_ID_KEY).await
+        .map_err(|e| format!("Session read error: {}", e))
+}
+
+/// Reads the session's CSRF token, if one has been issued.
+pub async fn session_csrf_token(session: &Session) -> Result<Option<String>, String> {
+    session.get(CSRF_TOKEN_KEY).await
+        .map_err(|e| format!("Session read error: {}", e))
+}
+
+/// Issues the session a new random CSRF token, replacing any previous one.
+async fn issue_csrf_token(session: &Session) -> Result<String, String> {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    session.insert(CSRF_TOKEN_KEY, &token).await
+        .map_err(|e| format!("Session write error: {}", e))?;
+    Ok(token)
+}
 
 #[derive(Deserialize)]
 pub struct Location {
     pub longitude: Option<f64>,
     pub latitude: Option<f64>,
+    /// Radius of uncertainty in metres, as reported by the browser.
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocationFix {
+    pub created: OffsetDateTime,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Admin,
+}
+
+impl Role {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "admin" => Role::Admin,
+            _ => Role::User,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -21,43 +74,194 @@ pub struct User {
     pub id: Uuid,
     pub latitude: Option<f64>,
     pub longitude: Option<f64>,
+    pub role: Role,
+    pub banned: bool,
+    /// Whether the user has consented to keeping a history of their fixes.
+    pub location_history: bool,
+    /// The CSRF token of the session the user came in with. Empty for
+    /// bearer-token requests, which don't need one.
+    pub csrf: String,
+}
+
+impl User {
+    pub fn is_admin(&self) -> bool {
+        self.role == Role::Admin
+    }
+}
+
+/// How far, and how long after the last stored fix, a user has to move
+/// before `update_location` records the new position.
+#[derive(Clone, Copy, Debug)]
+pub struct LocationThreshold {
+    pub min_distance: f64,
+    pub min_interval: Duration,
+}
+
+impl Default for LocationThreshold {
+    fn default() -> Self {
+        Self {
+            min_distance: 50.0,
+            min_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+impl LocationThreshold {
+    /// Reads `LOCATION_MIN_DISTANCE` (metres) and `LOCATION_MIN_INTERVAL`
+    /// (seconds), falling back to the defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok()).filter(|v| *v >= 0.0);
+        Self {
+            min_distance: var("LOCATION_MIN_DISTANCE").unwrap_or(default.min_distance),
+            min_interval: var("LOCATION_MIN_INTERVAL")
+                .map(Duration::from_secs_f64)
+                .unwrap_or(default.min_interval),
+        }
+    }
+}
+
+/// Limits on the consented location history kept per user.
+#[derive(Clone, Copy, Debug)]
+pub struct HistoryRetention {
+    pub max_age: Duration,
+    pub max_points: i64,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(30 * 24 * 60 * 60),
+            max_points: 1000,
+        }
+    }
+}
+
+impl HistoryRetention {
+    /// Reads `LOCATION_HISTORY_DAYS` and `LOCATION_HISTORY_MAX`, falling
+    /// back to the defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            max_age: std::env::var("LOCATION_HISTORY_DAYS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60))
+                .unwrap_or(default.max_age),
+            max_points: std::env::var("LOCATION_HISTORY_MAX")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default.max_points),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct UserService {
     pool: PgPool,
+    threshold: LocationThreshold,
+    retention: HistoryRetention,
 }
 
 impl UserService {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, threshold: LocationThreshold, retention: HistoryRetention) -> Self {
+        Self { pool, threshold, retention }
     }
 
     // This method handles the session-to-DB mapping completely.
     pub async fn get_or_create_session_user(&self, session: Session) -> Result<User, String> {
-        // 1. Try to load the application's User ID from the session store
-        let user_id_option: Option<Uuid> = session.get(APP_USER_ID_KEY).await
-            .map_err(|e| format!("Session read error: {}", e))?;
-
-        let final_user = if let Some(id) = user_id_option {
-            // A. User ID found: Load user from the database.
-            self.get_by_id(&id).await?
+        let final_user = if let Some(user) = self.get_session_user(&session).await? {
+            // A. User found: refresh when they were last seen.
+            self.touch(&session, &user.id).await?;
+            user
         } else {
-            // B. New Session: Create a new anonymous user.
+            // B. New session, or its user was erased: Create a new anonymous user.
             let new_user = self.create_anonymous_user().await?;
 
-            // C. Store the new user's ID in the tower-session object.
-            // This implicitly triggers the session middleware to generate a Session ID
-            // and send the Set-Cookie header in the response.
-            session.insert(APP_USER_ID_KEY, new_user.id).await
-                .map_err(|e| format!("Session write error: {}", e))?;
+            // C. Bind the new user to the session. This implicitly triggers the
+            // session middleware to send the Set-Cookie header in the response.
+            let csrf = self.login(&session, &new_user.id).await?;
 
-            new_user
+            User { csrf, ..new_user }
         };
 
         Ok(final_user)
     }
 
+    /// The session's user, with the session's CSRF token, or `None` if the
+    /// session has no user (any more). Never creates a user.
+    pub async fn get_session_user(&self, session: &Session) -> Result<Option<User>, String> {
+        let Some(id) = session_user_id(session).await? else {
+            return Ok(None);
+        };
+        let Some(user) = self.find(&id).await? else {
+            return Ok(None);
+        };
+
+        // Sessions from before tokens were kept in the session get one now.
+        let csrf = match session_csrf_token(session).await? {
+            Some(token) => token,
+            None => issue_csrf_token(session).await?,
+        };
+        Ok(Some(User { csrf, ..user }))
+    }
+
+    /// Binds a user to the session under a fresh session ID and CSRF token,
+    /// so neither an ID planted before the user was attached nor a token
+    /// leaked from an earlier session can be used to ride on it. Anything
+    /// that changes who a session belongs to should go through here.
+    /// Returns the new CSRF token.
+    pub async fn login(&self, session: &Session, id: &Uuid) -> Result<String, String> {
+        session.cycle_id().await
+            .map_err(|e| format!("Session write error: {}", e))?;
+        session.insert(APP_USER_ID_KEY, *id).await
+            .map_err(|e| format!("Session write error: {}", e))?;
+        session.insert(LAST_SEEN_KEY, OffsetDateTime::now_utc().unix_timestamp()).await
+            .map_err(|e| format!("Session write error: {}", e))?;
+        issue_csrf_token(session).await
+    }
+
+    /// Refreshes `users.last_seen`, at most once per `LAST_SEEN_INTERVAL`;
+    /// the session remembers when it last did so.
+    async fn touch(&self, session: &Session, id: &Uuid) -> Result<(), String> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let last_seen: Option<i64> = session.get(LAST_SEEN_KEY).await
+            .map_err(|e| format!("Session read error: {}", e))?;
+        if last_seen.is_some_and(|seen| now - seen < LAST_SEEN_INTERVAL.as_secs() as i64) {
+            return Ok(());
+        }
+
+        sqlx::query(r#"UPDATE users SET last_seen = NOW() WHERE id = $1"#)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        session.insert(LAST_SEEN_KEY, now).await
+            .map_err(|e| format!("Session write error: {}", e))
+    }
+
+    /// Deletes anonymous users who haven't been seen for longer than `idle`
+    /// and have nothing worth keeping: no joys and no API tokens. Banned
+    /// users are kept so the ban isn't lost. Returns how many were deleted.
+    pub async fn delete_abandoned(&self, idle: Duration) -> Result<u64, String> {
+        let result = sqlx::query(r#"
+            DELETE FROM users u
+            WHERE u.role = 'user'
+                AND u.banned IS NULL
+                AND u.last_seen < NOW() - make_interval(secs => $1)
+                AND NOT EXISTS (SELECT 1 FROM joys j WHERE j.user_id = u.id)
+                AND NOT EXISTS (SELECT 1 FROM api_tokens t WHERE t.user_id = u.id)
+        "#)
+            .bind(idle.as_secs_f64())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn create_anonymous_user(&self) -> Result<User, String> {
         let row = sqlx::query(r#"
             INSERT INTO users DEFAULT VALUES
@@ -71,72 +275,266 @@ impl UserService {
             id: row.get::<Uuid, _>("id"),
             latitude: None,
             longitude: None,
+            role: Role::User,
+            banned: false,
+            location_history: false,
+            csrf: String::new(),
         })
     }
 
-    pub async fn update_location(&self, id: &Uuid, longitude: f64, latitude: f64) -> Result<(), String> {
-        sqlx::query(
-            r#"UPDATE users SET point = ST_MakePoint($2, $3) WHERE id = $1"#
+    /// Stores the user's position if it is their first fix, or if they have
+    /// moved at least `min_distance` metres and the previous fix is older
+    /// than `min_interval`. Stored fixes are also appended to the user's
+    /// location history if they have consented to it. Returns whether the
+    /// position changed significantly; jitter below the threshold is dropped.
+    pub async fn update_location(
+        &self,
+        id: &Uuid,
+        longitude: f64,
+        latitude: f64,
+        accuracy: Option<f64>,
+    ) -> Result<bool, String> {
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        let row = sqlx::query(
+            r#"
+                UPDATE users SET
+                    point = ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography,
+                    accuracy = $6,
+                    located = NOW()
+                WHERE id = $1
+                    AND (
+                        point IS NULL
+                        OR located IS NULL
+                        OR (
+                            ST_Distance(point, ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography) >= $4
+                            AND located <= NOW() - make_interval(secs => $5)
+                        )
+                    )
+                RETURNING location_history
+            "#
         )
         .bind(id)
         .bind(longitude)
         .bind(latitude)
-        .execute(&self.pool)
+        .bind(self.threshold.min_distance)
+        .bind(self.threshold.min_interval.as_secs_f64())
+        .bind(accuracy)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(|e| e.to_string())?;
 
+        let Some(row) = row else {
+            return Ok(false);
+        };
+
+        if row.get::<bool, _>("location_history") {
+            sqlx::query(r#"
+                INSERT INTO user_locations (user_id, point, accuracy)
+                VALUES ($1, ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography, $4)
+            "#)
+            .bind(id)
+            .bind(longitude)
+            .bind(latitude)
+            .bind(accuracy)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+            sqlx::query(r#"
+                DELETE FROM user_locations
+                WHERE user_id = $1
+                    AND (
+                        created < NOW() - make_interval(secs => $2)
+                        OR id NOT IN (
+                            SELECT id FROM user_locations
+                            WHERE user_id = $1
+                            ORDER BY created DESC
+                            LIMIT $3
+                        )
+                    )
+            "#)
+            .bind(id)
+            .bind(self.retention.max_age.as_secs_f64())
+            .bind(self.retention.max_points)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    /// Sets a location the user picked by hand, e.g. from the gazetteer when
+    /// geolocation is unavailable. Skips the movement threshold and is not
+    /// recorded in the location history.
+    pub async fn set_manual_location(&self, id: &Uuid, longitude: f64, latitude: f64) -> Result<(), String> {
+        sqlx::query(r#"
+            UPDATE users SET
+                point = ST_SetSRID(ST_MakePoint($2, $3), 4326)::geography,
+                accuracy = NULL,
+                located = NOW()
+            WHERE id = $1
+        "#)
+            .bind(id)
+            .bind(longitude)
+            .bind(latitude)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
         Ok(())
     }
 
-    pub async fn get_by_id(&self, id: &Uuid) -> Result<User, String> {
-        let result = sqlx::query_as!(
-            User,
+    /// Forgets the user's last known location, e.g. when they stop sharing it.
+    pub async fn forget_location(&self, id: &Uuid) -> Result<(), String> {
+        sqlx::query(r#"
+            UPDATE users SET point = NULL, accuracy = NULL, located = NULL
+            WHERE id = $1
+        "#)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Turns the location history on or off. Turning it off also forgets
+    /// everything recorded so far.
+    pub async fn set_location_history(&self, id: &Uuid, enabled: bool) -> Result<(), String> {
+        let mut tx = self.pool.begin().await.map_err(|e| e.to_string())?;
+
+        sqlx::query(r#"UPDATE users SET location_history = $2 WHERE id = $1"#)
+            .bind(id)
+            .bind(enabled)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !enabled {
+            sqlx::query(r#"DELETE FROM user_locations WHERE user_id = $1"#)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        tx.commit().await.map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn location_history(&self, id: &Uuid, limit: i64) -> Result<Vec<LocationFix>, String> {
+        let rows = sqlx::query(r#"
+            SELECT
+                created,
+                ST_X(point::geometry) AS longitude,
+                ST_Y(point::geometry) AS latitude,
+                accuracy
+            FROM user_locations
+            WHERE user_id = $1
+            ORDER BY created DESC
+            LIMIT $2
+        "#)
+            .bind(id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| LocationFix {
+                created: row.get::<OffsetDateTime, _>("created"),
+                longitude: row.get::<f64, _>("longitude"),
+                latitude: row.get::<f64, _>("latitude"),
+                accuracy: row.get::<Option<f64>, _>("accuracy"),
+            })
+            .collect())
+    }
+
+    /// The user with this ID, or `None` if there is none, e.g. because a
+    /// session outlived its user's erasure.
+    pub async fn find(&self, id: &Uuid) -> Result<Option<User>, String> {
+        let row = sqlx::query(
             r#"
                 SELECT
                     id,
                     ST_X(point::geometry) AS longitude,
-                    ST_Y(point::geometry) AS latitude
+                    ST_Y(point::geometry) AS latitude,
+                    role,
+                    banned IS NOT NULL AS banned,
+                    location_history
                 FROM users WHERE id = $1
             "#,
-            id
         )
+        .bind(id)
         .fetch_optional(&self.pool) // 2. Use fetch_optional to get 0 or 1 row.
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "User not found".to_string())?;
-        Ok(result)
+        .map_err(|e| e.to_string())?;
+
+        Ok(row.map(|row| User {
+            id: row.get::<Uuid, _>("id"),
+            longitude: row.get::<Option<f64>, _>("longitude"),
+            latitude: row.get::<Option<f64>, _>("latitude"),
+            role: Role::parse(&row.get::<String, _>("role")),
+            banned: row.get::<bool, _>("banned"),
+            location_history: row.get::<bool, _>("location_history"),
+            csrf: String::new(),
+        }))
+    }
+
+    pub async fn get_by_id(&self, id: &Uuid) -> Result<User, String> {
+        self.find(id).await?
+            .ok_or_else(|| "User not found".to_string())
     }
 }
 
+/// Records a fix reported by the client, unless the user has turned
+/// location sharing off. Returns whether it was stored; a stored fix
+/// re-renders their feed once the burst of fixes settles.
+pub async fn record_location(
+    state: &AppState,
+    user_id: Uuid,
+    longitude: f64,
+    latitude: f64,
+    accuracy: Option<f64>,
+) -> Result<bool, String> {
+    let preferences = state.preferences.get(&user_id).await?;
+    if !preferences.share_location {
+        return Ok(false);
+    }
+
+    if !state.users.update_location(&user_id, longitude, latitude, accuracy).await? {
+        return Ok(false);
+    }
+
+    // GPS fixes arrive in bursts; only re-render once they settle.
+    let sse = state.sse.clone();
+    state.location_debounce.trigger(user_id, move || {
+        if let Err(e) = sse.publish(Patch::JoyCards { user_id: Some(user_id) }) {
+            tracing::warn!(?e, "failed to publish SSE patch");
+        }
+    });
+
+    Ok(true)
+}
+
 pub async fn update_user(
     State(state): State<AppState>,
-    session: Session,
+    CurrentUser(user): CurrentUser,
     Json(form): Json<Location>,
 ) -> Result<StatusCode, StatusCode> {
-    let user = state.users.get_or_create_session_user(session).await
-        .map_err(|e| {
-            tracing::error!("Failed to get or create session user: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    if let (Some(lon), Some(lat)) = (form.longitude, form.latitude) {
-        state.users.update_location(&user.id, lat, lon).await
-            .map_err(|e| {
-                tracing::error!("Failed to update user location: {}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-    }
+    let (Some(lon), Some(lat)) = (form.longitude, form.latitude) else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
 
-    let Html(cards) = crate::component::joy_cards::render_for_user(&state, user.id)
-        .await
+    record_location(&state, user.id, lon, lat, form.accuracy).await
         .map_err(|e| {
-            tracing::error!("Failed to render joy cards: {}", e);
+            tracing::error!("Failed to update user location: {}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    if let Err(e) = state.sse.publish_html(cards) {
-        tracing::warn!(?e, "failed to publish SSE html");
-    }
-
     Ok(StatusCode::NO_CONTENT)
 }
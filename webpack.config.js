@@ -6,7 +6,7 @@ const CopyWebpackPlugin = require('copy-webpack-plugin');
 
 function components() {
   const components = {};
-  for (const component of ['app', 'joy_form', 'joy_cards', 'joy_card']) {
+  for (const component of ['app', 'joy_form', 'joy_cards', 'joy_card', 'admin', 'flash', 'timeline', 'location_picker', 'settings']) {
     components[`css/component/${component}`] = path.resolve(__dirname, 'src', 'component', component, `${component}.scss`);
   }
   return components;
@@ -50,10 +50,11 @@ module.exports = {
       ALIAS: JSON.stringify(null),
     }),
     new MiniCssExtractPlugin({ filename: '[name].css' }),
+    // index.html loads the bundle itself, so the tags carry the CSP nonce.
     new HtmlWebpackPlugin({
       template: path.resolve(__dirname, 'src', 'index.html'),
       filename: '../index.html',
-      inject: 'body',
+      inject: false,
     }),
     new CopyWebpackPlugin({
       patterns: [